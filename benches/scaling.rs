@@ -0,0 +1,58 @@
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+
+use xmascode::day1::{SetIterator, Strategy};
+use xmascode::day3::{bitset, dense};
+use xmascode::generate::{self, Rng};
+
+fn generated(write: impl FnOnce(&mut Rng, &mut Vec<u8>) -> std::io::Result<()>) -> String {
+    let mut out = Vec::new();
+    write(&mut Rng::new(2020), &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+pub fn find_n_sum(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day1 find_n_sum");
+    for &count in &[200, 1000, 5000] {
+        let generator = generate::Expenses { count, k: 3, target: 2020, max: count as i64 * 4 };
+        let input = generated(|rng, out| generator.write(rng, out));
+        let items = xmascode::day1::items(&input).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(count), &items, |b, items| {
+            b.iter(|| black_box(items.iter().find_n_sum(3, 2020)))
+        });
+    }
+    group.finish();
+}
+
+pub fn strategies(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day1 strategies");
+    let generator = generate::Expenses { count: 1000, k: 3, target: 2020, max: 4000 };
+    let input = generated(|rng, out| generator.write(rng, out));
+    let items = xmascode::day1::items(&input).unwrap();
+    for &strategy in &Strategy::ALL {
+        group.bench_with_input(BenchmarkId::from_parameter(strategy), &items, |b, items| {
+            b.iter(|| black_box(items.iter().find_n_sum_by(strategy, 3, 2020)))
+        });
+    }
+    group.finish();
+}
+
+pub fn grids(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day3 grids");
+    for &(width, height) in &[(31, 323), (256, 4096), (1024, 16384)] {
+        let generator = generate::Forest { width, height, density: 0.2 };
+        let input = generated(|rng, out| generator.write(rng, out));
+        let size = format!("{}x{}", width, height);
+        let grid: dense::Grid = input.parse().unwrap();
+        group.bench_with_input(BenchmarkId::new("dense", &size), &grid, |b, grid| {
+            b.iter(|| black_box(grid.route((3, 1)).filter(|&t| t == dense::Tile::Tree).count()))
+        });
+        let grid: bitset::Grid = input.parse().unwrap();
+        group.bench_with_input(BenchmarkId::new("bitset", &size), &grid, |b, grid| {
+            b.iter(|| black_box(bitset::count_hits(grid, grid.each(0, 3, 1))))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, find_n_sum, strategies, grids);
+criterion_main!(benches);
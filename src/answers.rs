@@ -0,0 +1,95 @@
+//! Manifest of known puzzle answers, used to catch regressions across solvers.
+//!
+//! The manifest is a plain text file with an entry per line, in the form of
+//! `<day> <part> <input> <answer>`. Empty lines and lines starting with `#`
+//! are ignored.
+
+use std::str::FromStr;
+
+use crate::{Answer, ParseError, Part};
+
+/// Conventional location of the answers manifest.
+pub const MANIFEST: &str = "input/answers";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expected {
+    pub day: u8,
+    pub part: Part,
+    pub input: String,
+    pub answer: Answer
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+    entries: Vec<Expected>
+}
+
+impl Manifest {
+
+    pub fn entries(&self) -> &[Expected] {
+        &self.entries
+    }
+
+    /// Distinct inputs having at least one answer for the given day, in the
+    /// order of appearance.
+    pub fn inputs(&self, day: u8) -> Vec<&str> {
+        let mut inputs: Vec<&str> = Vec::new();
+        for entry in self.entries.iter().filter(|e| e.day == day) {
+            if !inputs.contains(&entry.input.as_str()) {
+                inputs.push(&entry.input)
+            }
+        }
+        inputs
+    }
+
+    pub fn lookup(&self, day: u8, part: Part, input: &str) -> Option<Answer> {
+        self.entries.iter()
+            .find(|e| e.day == day && e.part == part && e.input == input)
+            .map(|e| e.answer)
+    }
+
+}
+
+impl FromStr for Expected {
+
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        match fields.as_slice() {
+            [day, part, input, answer] => {
+                let day = day.parse()
+                    .map_err(|_| ParseError::new("Invalid day").expected("day number").found(*day))?;
+                let part = match *part {
+                    "1" => Part::One,
+                    "2" => Part::Two,
+                    _ => return Err(ParseError::new("Invalid part").expected("1 or 2").found(*part))
+                };
+                let answer = answer.parse()
+                    .map_err(|_| ParseError::new("Invalid answer").expected("integer").found(*answer))?;
+                Ok(Expected { day, part, input: input.to_string(), answer })
+            },
+            _ => Err(ParseError::new("Invalid entry")
+                .expected("`<day> <part> <input> <answer>`")
+                .found(format!("{} fields", fields.len())))
+        }
+    }
+
+}
+
+impl FromStr for Manifest {
+
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let entries = s.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .map(|(idx, line)| {
+                line.parse().map_err(|e: ParseError| e.at_line(idx + 1).with_snippet(line))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Manifest { entries })
+    }
+
+}
@@ -1,84 +1,519 @@
-use std::collections::btree_set::BTreeSet;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::ops::{Add, Sub};
-
-type Value = i64;
-type Set = BTreeSet<Value>;
-
-trait Integer: Add<Output = Self> + Sub<Output = Self> + Ord + Eq + Copy {}
-impl<T: Add<Output = Self> + Sub<Output = Self> + Ord + Eq + Copy> Integer for T {}
-
-// static mut ITERATIONS: u64 = 0;
-static ITERATIONS: AtomicUsize = AtomicUsize::new(0);
-
-trait SetIterator<I: Integer>: DoubleEndedIterator<Item = I> {
-
-    fn find_n_sum(mut self, n: u8, target: I) -> Option<Vec<I>> where
-        Self: Sized + Clone
-    {
-        match n {
-            0..=1 => None,
-            2     => {
-                let mut head = self.next();
-                let mut tail = self.next_back();
-                while head.is_some() && tail.is_some() {
-                    let _ = ITERATIONS.fetch_add(1, Ordering::Relaxed);
-                    let h = head.unwrap();
-                    let t = tail.unwrap();
-                    let sum = h + t;
-                    if sum == target {
-                        return Some(vec![h, t]);
-                    }
-                    if sum < target {
-                        head = self.next();
-                    }
-                    if sum > target {
-                        tail = self.next_back();
-                    }
+use xmascode::{Error, Format, Source};
+use xmascode::day1::{self, Multiset, SetIterator, Strategy, Value};
+use std::cmp::Ordering;
+use std::io::BufRead;
+use std::ops::RangeInclusive;
+use xmascode::json::Json;
+use xmascode::metrics::{self, Kind, Snapshot};
+
+const USAGE: &str = "\
+Usage: day1 [--k N]... [--strategy NAME | --threads N | --trace] [--within LO HI]
+            [MODE] [--format text|json] [INPUT] [TARGET]
+
+Reads expenses from INPUT, or from stdin if it's `-` or missing.
+TARGET defaults to 2020. Looks for the first combination of expenses summing
+up to TARGET, unless some other MODE is given.
+
+    --k N       Look for N expenses summing up to TARGET, 3 by default;
+                N of 1 looks for TARGET itself. Repeat the option or give
+                a comma separated list to search for several N in a row
+    --strategy NAME
+                Search for the first combination with two-pointer
+                (default), hash-set, indexed or meet-in-the-middle
+    --threads N Search for the first combination with two-pointer scans
+                split across N threads
+    --trace     Search for the first combination with two-pointer scans,
+                printing every head fixed and every pointer move on the way
+    --within LO HI
+                Look for combinations summing up to anything between LO and
+                HI inclusive instead of TARGET; lists all of them unless
+                --count is given
+
+Modes:
+    --all       List every distinct combination summing up to TARGET
+    --count     Only tell how many distinct combinations there are
+    --nearest   Find the combination summing up closest to TARGET
+    --at-most   Find the combination summing up closest to TARGET without
+                going over it
+    --run       Find a run of at least two consecutive expenses summing up
+                to TARGET, along with its smallest and largest expense
+    --stream    Read expenses one by one, reporting pairs and triples summing
+                up to TARGET as soon as the expense completing them comes
+                in; --k 2 or --k 3 only reports pairs or triples
+    --subsets BOUND
+                Tell which sums up to BOUND subsets of expenses add up to,
+                and find a subset summing up to TARGET; subsets of any
+                size unless --k is given";
+
+/// What to look for among the expenses.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Mode {
+    First,
+    All,
+    Count,
+    Nearest { at_most: bool },
+    Run,
+    Stream,
+    Subsets(Value)
+}
+
+/// Single search, for one specific `k`, if any.
+struct Search<'a> {
+    source: &'a Source,
+    items: &'a Multiset,
+    target: Value,
+    k: Option<u8>,
+    strategy: Option<Strategy>,
+    threads: Option<usize>,
+    trace: bool,
+    within: Option<RangeInclusive<Value>>,
+    format: Format
+}
+
+fn parse_k(arg: &str) -> Result<Vec<u8>, Error> {
+    arg.split(',')
+        .map(|k| match k.trim().parse() {
+            Ok(0) | Err(_) => Err(Error::usage(format!("Invalid number of expenses: {}", k))),
+            Ok(k) => Ok(k)
+        })
+        .collect()
+}
+
+fn run(args: Vec<String>, format: Format) -> Result<(), Error> {
+    let mut mode = Mode::First;
+    let mut ks = Vec::new();
+    let mut strategy = None;
+    let mut threads = None;
+    let mut trace = false;
+    let mut within = None;
+    let mut positional = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" | "--count" | "--nearest" | "--at-most" | "--run" | "--stream" | "--subsets" if mode != Mode::First => {
+                return Err(Error::usage("Only a single mode can be given"));
+            },
+            "--all" => mode = Mode::All,
+            "--count" => mode = Mode::Count,
+            "--nearest" => mode = Mode::Nearest { at_most: false },
+            "--at-most" => mode = Mode::Nearest { at_most: true },
+            "--run" => mode = Mode::Run,
+            "--stream" => mode = Mode::Stream,
+            "--subsets" => {
+                let value = args.next().ok_or_else(|| Error::usage("Please provide bound"))?;
+                let bound = value.parse().map_err(|_| Error::usage("Bound must be an integer"))?;
+                mode = Mode::Subsets(bound);
+            },
+            "--within" => {
+                let mut bound = || -> Result<Value, Error> {
+                    let value = args.next().ok_or_else(|| Error::usage("Please provide range bounds"))?;
+                    value.parse().map_err(|_| Error::usage("Range bounds must be integers"))
+                };
+                within = Some(bound()?..=bound()?);
+            },
+            "--threads" => {
+                let value = args.next().ok_or_else(|| Error::usage("Please provide number of threads"))?;
+                match value.parse() {
+                    Ok(0) | Err(_) => return Err(Error::usage(format!("Invalid number of threads: {}", value))),
+                    Ok(n) => threads = Some(n)
                 }
-                None
             },
-            _     => {
-                let mut head = self.next();
-                while head.is_some() {
-                    let h = head.unwrap();
-                    if let Some(mut found) = self.clone().find_n_sum(n - 1, target - h) {
-                        found.push(h);
-                        return Some(found);
-                    }
-                    head = self.next();
+            "--trace" => trace = true,
+            "--strategy" => {
+                let value = args.next().ok_or_else(|| Error::usage("Please provide strategy"))?;
+                strategy = Some(value.parse()?);
+            },
+            "--k" => {
+                let value = args.next().ok_or_else(|| Error::usage("Please provide number of expenses"))?;
+                ks.extend(parse_k(&value)?);
+            },
+            _ if arg.starts_with("--") => return Err(Error::usage(format!("Unknown option: {}", arg))),
+            _ => positional.push(arg)
+        }
+    }
+    if (strategy.is_some() || threads.is_some()) && (mode != Mode::First || within.is_some()) {
+        return Err(Error::usage("Options --strategy and --threads only apply when looking for a single combination"));
+    }
+    if trace && (mode != Mode::First || within.is_some()) {
+        return Err(Error::usage("Option --trace only applies when looking for a single combination"));
+    }
+    if trace && (strategy.is_some() || threads.is_some()) {
+        return Err(Error::usage("Only the sequential two-pointer search can be traced"));
+    }
+    if threads.is_some() && strategy.is_some_and(|strategy| strategy != Strategy::TwoPointer) {
+        return Err(Error::usage("Only two-pointer scans can be split across threads"));
+    }
+    if within.is_some() {
+        match mode {
+            Mode::First => mode = Mode::All,
+            Mode::All | Mode::Count => {},
+            _ => return Err(Error::usage("Option --within only applies to listing or counting combinations"))
+        }
+        if positional.len() > 1 {
+            return Err(Error::usage("Option --within replaces TARGET"));
+        }
+    }
+    if mode == Mode::Run && !ks.is_empty() {
+        return Err(Error::usage("Runs of expenses can be of any length"));
+    }
+    if mode == Mode::Stream && ks.iter().any(|&k| k != 2 && k != 3) {
+        return Err(Error::usage("Only pairs and triples can be streamed"));
+    }
+    let ks = match (ks.is_empty(), mode) {
+        (true, Mode::Stream) => vec![Some(2), Some(3)],
+        (true, Mode::Subsets(_)) | (true, Mode::Run) => vec![None],
+        (true, _) => vec![Some(3)],
+        (false, _) => ks.into_iter().map(Some).collect()
+    };
+    let mut args = positional.into_iter();
+    let source = Source::from_arg(args.next());
+    let target: Value = match args.next() {
+        Some(target) => target.parse().map_err(|_| Error::usage("Target sum must be an integer"))?,
+        None => day1::TARGET
+    };
+    if mode == Mode::Stream {
+        return stream(&source, target, &ks, format);
+    }
+    let input = source.read_to_string()?;
+    let items = day1::items(&input).map_err(|e| e.in_file(source.name()))?;
+    let several = ks.len() > 1;
+    let mut missing = Vec::new();
+    for &k in &ks {
+        metrics::reset();
+        day1::EXPENSES.set(items.len());
+        if let (true, Format::Text, Some(k)) = (several, format, k) {
+            println!("k = {}", k);
+        }
+        let search = Search { source: &source, items: &items, target, k, strategy, threads, trace, within: within.clone(), format };
+        let found = match mode {
+            Mode::First => search.first()?,
+            Mode::All => search.all(),
+            Mode::Count => search.count(),
+            Mode::Nearest { at_most } => search.nearest(at_most),
+            Mode::Run => search.run()?,
+            Mode::Subsets(bound) => search.subsets(bound)?,
+            Mode::Stream => unreachable!("streaming does not read the whole input up front")
+        };
+        if !found {
+            missing.push(k);
+        }
+    }
+    match missing.as_slice() {
+        [] => Ok(()),
+        _ if mode == Mode::Run => Err(Error::NoSolution(format!("no run of expenses sums up to {}", target))),
+        _ if mode == Mode::Nearest { at_most: true } => {
+            Err(Error::NoSolution(format!("no expenses sum up to at most {}", target)))
+        },
+        [None] => Err(Error::NoSolution(format!("no subset of expenses sums up to {}", target))),
+        _ => {
+            let missing: Vec<_> = missing.iter().flatten().map(|k| k.to_string()).collect();
+            let goal = match within {
+                Some(range) => format!("between {} and {}", range.start(), range.end()),
+                None => target.to_string()
+            };
+            Err(Error::NoSolution(format!("no {} expenses sum up to {}", missing.join(" or "), goal)))
+        }
+    }
+}
+
+/// Feeds expenses to the detector as soon as they are read, reporting what
+/// each of them completes right away.
+fn stream(source: &Source, target: Value, ks: &[Option<u8>], format: Format) -> Result<(), Error> {
+    let mut detector = day1::Detector::new(target);
+    let mut hits = 0;
+    for (idx, line) in source.open()?.lines().enumerate() {
+        let line = line.map_err(|e| Error::io(source.name(), e))?;
+        let value = day1::parse_expense(&line)
+            .map_err(|e| e.at_line(idx + 1).with_snippet(&line).in_file(source.name()))?;
+        day1::EXPENSES.set(detector.len() + 1);
+        for found in detector.insert(value) {
+            if !ks.contains(&Some(found.len() as u8)) {
+                continue;
+            }
+            hits += 1;
+            let product = day1::product(&found);
+            match format {
+                Format::Json => println!("{}", Json::object()
+                    .with("day", 1)
+                    .with("input", source.name())
+                    .with("target", target)
+                    .with("line", idx + 1)
+                    .with("found", found)
+                    .with("product", product)),
+                _ => match product {
+                    Some(product) => println!("line {}: {:?} product {}", idx + 1, found, product),
+                    None => println!("line {}: {:?} product overflows", idx + 1, found)
                 }
-                None
             }
         }
     }
+    match format {
+        Format::Json => println!("{}", Json::object()
+            .with("day", 1)
+            .with("input", source.name())
+            .with("target", target)
+            .with("count", hits)
+            .with("metrics", Snapshot::take().to_json())),
+        _ => println!("Found: {} after {} expenses", hits, detector.len())
+    }
+    match hits {
+        0 => Err(Error::NoSolution(format!("no pair or triple of expenses sums up to {}", target))),
+        _ => Ok(())
+    }
+}
 
+/// Prints how much work the search did: iterations, along with whatever
+/// other counters it bumped, such as lookups of the hash set strategies.
+fn print_counters() {
+    println!("Total iterations: {}", day1::iterations());
+    for &metric in day1::METRICS {
+        if metric.kind() != Kind::Counter || std::ptr::eq(metric, &day1::ITERATIONS) || metric.get() == 0 {
+            continue;
+        }
+        let name = metric.name().trim_start_matches("day1.");
+        println!("Total {}: {}", name, metric.get());
+    }
 }
 
-impl<T, I: Integer> SetIterator<I> for T where T: DoubleEndedIterator<Item = I> {}
+impl Search<'_> {
+
+    fn k(&self) -> u8 {
+        self.k.unwrap_or(3)
+    }
+
+    fn json(&self) -> Json {
+        let json = Json::object()
+            .with("day", 1)
+            .with("input", self.source.name())
+            .with("k", self.k);
+        match &self.within {
+            Some(range) => json.with("within", (*range.start(), *range.end())),
+            None => json.with("target", self.target)
+        }
+    }
+
+    fn first(&self) -> Result<bool, Error> {
+        let mut trace = day1::Trace::new();
+        let result = match self.threads {
+            Some(threads) => self.items.iter().par_find_n_sum(self.k(), self.target, threads),
+            None if self.trace => self.items.iter().find_n_sum_traced(self.k(), self.target, &mut trace),
+            None => self.items.iter().find_n_sum_by(self.strategy.unwrap_or_default(), self.k(), self.target)
+        };
+        let product = result.as_deref().and_then(day1::product);
+        let lines = result.as_ref().and_then(|found| self.items.lines_of(found));
+        match self.format {
+            Format::Json => {
+                println!("{}", self.json()
+                    .with("strategy", self.strategy.unwrap_or_default().name())
+                    .with("threads", self.threads)
+                    .with("answer", product)
+                    .with("found", result.clone())
+                    .with("lines", lines)
+                    .with("trace", if self.trace { trace.to_json() } else { Json::Null })
+                    .with("metrics", Snapshot::take().to_json()));
+            },
+            _ => {
+                print!("{}", trace);
+                println!("{:?}", result);
+                if let Some(lines) = lines {
+                    println!("Lines: {:?}", lines)
+                }
+                if let Some(product) = product {
+                    println!("Product: {}", product)
+                }
+                print_counters();
+            }
+        }
+        match (&result, product) {
+            (Some(found), None) => Err(Error::Overflow(format!("product of {:?} does not fit into 64 bits", found))),
+            _ => Ok(result.is_some())
+        }
+    }
+
+    fn all(&self) -> bool {
+        let mut listed = Vec::new();
+        let mut total = 0;
+        let solutions: Box<dyn Iterator<Item = Vec<Value>>> = match &self.within {
+            Some(range) => Box::new(self.items.iter().range_sums(self.k(), range.clone())),
+            None => Box::new(self.items.iter().n_sums(self.k(), self.target))
+        };
+        for found in solutions {
+            total += 1;
+            let product = day1::product(&found);
+            let lines = self.items.lines_of(&found);
+            match self.format {
+                Format::Json => listed.push(Json::object()
+                    .with("found", found)
+                    .with("lines", lines)
+                    .with("product", product)),
+                _ => match product {
+                    Some(product) => println!("{:?} lines {:?} product {}", found, lines.unwrap_or_default(), product),
+                    None => println!("{:?} lines {:?} product overflows", found, lines.unwrap_or_default())
+                }
+            }
+        }
+        match self.format {
+            Format::Json => {
+                println!("{}", self.json()
+                    .with("count", total)
+                    .with("solutions", listed)
+                    .with("metrics", Snapshot::take().to_json()));
+            },
+            _ => {
+                println!("Solutions: {}", total);
+                print_counters();
+            }
+        }
+        total > 0
+    }
+
+    fn count(&self) -> bool {
+        let count = match &self.within {
+            Some(range) => self.items.iter().count_range_sums(self.k(), range.clone()),
+            None => self.items.iter().count_n_sum(self.k(), self.target)
+        };
+        match self.format {
+            Format::Json => {
+                println!("{}", self.json()
+                    .with("count", count)
+                    .with("metrics", Snapshot::take().to_json()));
+            },
+            _ => {
+                println!("Solutions: {}", count);
+                print_counters();
+            }
+        }
+        count > 0
+    }
+
+    fn nearest(&self, at_most: bool) -> bool {
+        let nearest = self.items.iter().nearest_n_sum(self.k(), self.target, at_most);
+        let found = nearest.as_ref().map(|nearest| &nearest.found);
+        let lines = found.and_then(|found| self.items.lines_of(found));
+        // Signed distance from the sum to the target, so that it fits for any sum.
+        let residual = nearest.as_ref().map(|nearest| match nearest.ordering {
+            Ordering::Greater => -(nearest.residual as i128),
+            _ => nearest.residual as i128
+        });
+        match self.format {
+            Format::Json => {
+                println!("{}", self.json()
+                    .with("at_most", at_most)
+                    .with("found", found.cloned())
+                    .with("lines", lines)
+                    .with("residual", residual)
+                    .with("metrics", Snapshot::take().to_json()));
+            },
+            _ => {
+                println!("{:?}", found);
+                if let Some(lines) = lines {
+                    println!("Lines: {:?}", lines)
+                }
+                if let Some(nearest) = &nearest {
+                    match nearest.ordering {
+                        Ordering::Less => println!("Residual: {} under the target", nearest.residual),
+                        Ordering::Equal => println!("Residual: none, right on target"),
+                        Ordering::Greater => println!("Residual: {} over the target", nearest.residual)
+                    }
+                }
+                print_counters();
+            }
+        }
+        nearest.is_some()
+    }
+
+    fn run(&self) -> Result<bool, Error> {
+        let run = day1::find_run(&self.items.in_order(), self.target);
+        let sum = run.as_ref().map(|run| run.min_max_sum());
+        match self.format {
+            Format::Json => {
+                println!("{}", self.json()
+                    .with("answer", sum.flatten())
+                    .with("lines", run.as_ref().map(|run| (run.start + 1, run.end + 1)))
+                    .with("min", run.as_ref().map(|run| run.min))
+                    .with("max", run.as_ref().map(|run| run.max))
+                    .with("metrics", Snapshot::take().to_json()));
+            },
+            _ => {
+                if let Some(run) = &run {
+                    println!("Lines: {}-{}", run.start + 1, run.end + 1);
+                    println!("Min: {}, max: {}", run.min, run.max);
+                }
+                else {
+                    println!("None");
+                }
+                if let Some(Some(sum)) = sum {
+                    println!("Sum: {}", sum);
+                }
+                print_counters();
+            }
+        }
+        match (run, sum) {
+            (Some(run), Some(None)) => Err(Error::Overflow(format!("{} + {} does not fit into 64 bits", run.min, run.max))),
+            (run, _) => Ok(run.is_some())
+        }
+    }
+
+    fn subsets(&self, bound: Value) -> Result<bool, Error> {
+        let sums = day1::SubsetSums::new(self.items.values(), bound, self.k).map_err(|e| match e {
+            Error::Parse(e) => {
+                let line = self.items.iter()
+                    .find(|&v| v < 0)
+                    .and_then(|v| self.items.lines_of(&[v]))
+                    .and_then(|lines| lines.first().copied());
+                let e = match line {
+                    Some(line) => e.at_line(line),
+                    None => e
+                };
+                Error::Parse(e.in_file(self.source.name()))
+            },
+            e => e
+        })?;
+        let witness = sums.witness(self.target);
+        let lines = witness.as_ref().and_then(|found| self.items.lines_of(found));
+        let ranges = sums.ranges();
+        match self.format {
+            Format::Json => {
+                let ranges: Vec<_> = ranges.into_iter().map(|r| (*r.start(), *r.end())).collect();
+                println!("{}", self.json()
+                    .with("bound", bound)
+                    .with("count", sums.count())
+                    .with("reachable", ranges)
+                    .with("found", witness.clone())
+                    .with("lines", lines)
+                    .with("metrics", Snapshot::take().to_json()));
+            },
+            _ => {
+                let ranges: Vec<_> = ranges.into_iter()
+                    .map(|r| match r.start() == r.end() {
+                        true => r.start().to_string(),
+                        false => format!("{}-{}", r.start(), r.end())
+                    })
+                    .collect();
+                match ranges.is_empty() {
+                    true => println!("Reachable: no sums up to {}", bound),
+                    false => println!("Reachable: {} sums up to {}: {}", sums.count(), bound, ranges.join(", "))
+                }
+                println!("{:?}", witness);
+                if let Some(lines) = lines {
+                    println!("Lines: {:?}", lines)
+                }
+                println!("Total shifts: {}", day1::SHIFTS.get());
+            }
+        }
+        Ok(witness.is_some())
+    }
 
-fn items<P: AsRef<std::path::Path>>(path: P) -> Set {
-    let input = std::fs::read_to_string(path).unwrap();
-    input
-        .lines()
-        .map(|line| line.parse().unwrap())
-        .collect()
 }
 
 pub fn main() {
-    let mut args = std::env::args();
-    let path = args.nth(1)
-        .expect("Please provide path to the input file");
-    let target: Value = args.next()
-        .expect("Please provide target expense sum")
-        .parse()
-        .expect("Target sum must be an integer");
-    let items = items(path);
-    let result = items.iter().copied().find_n_sum(3, target);
-    println!("{:?}", result);
-    match result {
-        Some(found) => println!("Product: {}", found.iter().product::<i64>()),
-        None => {}
-    }
-    println!("Total iterations: {}", ITERATIONS.load(Ordering::Acquire));
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let format = match Format::take(&mut args) {
+        Ok(Some(Format::Tsv)) => Error::usage("Only text and json formats are supported").exit(USAGE, Format::Text),
+        Ok(format) => format.unwrap_or(Format::Text),
+        Err(e) => e.exit(USAGE, Format::Text)
+    };
+    if let Err(e) = run(args, format) {
+        e.exit(USAGE, format)
+    }
 }
@@ -1,56 +1,111 @@
-extern crate peg;
+use std::io::BufRead;
 
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::ops::RangeInclusive;
+use xmascode::{Error, Format, ParseError, Source};
+use xmascode::day2::{self, PolicyRule};
+use xmascode::json::Json;
 
-#[derive(Debug)]
-pub struct Policy {
-    range: RangeInclusive<usize>,
-    c: char
+const USAGE: &str = "\
+Usage: day2 [--rule NAME]... [--lenient] [--format text|json] [INPUT]
+
+Reads password policies from INPUT, or from stdin if it's `-` or missing.
+Besides `1-3 a` policies may use character classes like `[a-f]` or `digit`,
+require `min-length 8`, and combine several requirements with commas, `and`,
+`or`, `not` and parentheses.
+
+    --rule NAME Count passwords valid under the occurrences (part 1) or the
+                positions (part 2) rule, positions by default. Repeat the
+                option or give a comma separated list to check several
+                rules in a single pass
+    --lenient   Skip malformed lines instead of giving up on the first one,
+                warning about each and counting them";
+
+fn parse_rules(arg: &str) -> Result<Vec<&'static dyn PolicyRule>, Error> {
+    arg.split(',').map(|name| day2::rule(name.trim())).collect()
 }
 
-peg::parser! {
-    grammar password_policy_parser() for str {
-        rule __()
-            = [' ' | '\t']+
-        rule _()
-            = [' ' | '\t']*
-        rule char() -> char
-            = c:$([_]) { c.chars().next().unwrap() }
-        rule integer() -> usize
-            = n:$(['1'..='9']['0'..='9']+ / ['0'..='9']) {? n.parse().or(Err("usize")) }
-        rule policy() -> Policy
-            = from:integer() "-" to:integer() __ c:char() { Policy {range: from..=to, c} }
-        pub rule root() -> (Policy, &'input str)
-            = p:policy() _ ":" _ pwd:$([^ ' ' | '\t']+) { (p, pwd) }
+fn run(args: Vec<String>, format: Format) -> Result<(), Error> {
+    let mut rules = Vec::new();
+    let mut lenient = false;
+    let mut positional = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rule" => {
+                let value = args.next().ok_or_else(|| Error::usage("Please provide rule"))?;
+                rules.extend(parse_rules(&value)?);
+            },
+            "--lenient" => lenient = true,
+            _ if arg.starts_with("--") => return Err(Error::usage(format!("Unknown option: {}", arg))),
+            _ => positional.push(arg)
+        }
+    }
+    if rules.is_empty() {
+        rules.push(&day2::Positions);
     }
+    let source = Source::from_arg(positional.into_iter().next());
+    let mut tally = day2::Tally::new(rules);
+    let mut skipped = Vec::new();
+    let lines = source.open()?.lines();
+    for (idx, line) in lines.enumerate() {
+        let line = line.map_err(|e| Error::io(source.name(), e))?;
+        match day2::parse_line(&line) {
+            Ok((expr, password)) => tally.check(&expr, password),
+            Err(e) => {
+                let e = e.at_line(idx + 1).in_file(source.name());
+                if !lenient {
+                    return Err(e.into());
+                }
+                if format != Format::Json {
+                    eprintln!("Skipped {}", e);
+                }
+                skipped.push(e);
+            }
+        }
+    }
+    let skipped_json = || Json::Array(skipped.iter().map(ParseError::to_json).collect());
+    let counts: Vec<_> = tally.counts().collect();
+    match (format, counts.as_slice()) {
+        (Format::Json, [(rule, valid)]) => {
+            println!("{}", Json::object()
+                .with("day", 2)
+                .with("part", rule.part())
+                .with("rule", rule.name())
+                .with("input", source.name())
+                .with("answer", *valid)
+                .with("total", tally.total())
+                .with("skipped", skipped_json()));
+        },
+        (Format::Json, _) => {
+            let valid = counts.iter()
+                .fold(Json::object(), |json, (rule, valid)| json.with(rule.name(), *valid));
+            println!("{}", Json::object()
+                .with("day", 2)
+                .with("input", source.name())
+                .with("valid", valid)
+                .with("total", tally.total())
+                .with("skipped", skipped_json()));
+        },
+        (_, [(_, valid)]) => println!("Valid: {}", valid),
+        _ => {
+            for (rule, valid) in &counts {
+                println!("Valid by {}: {}", rule.name(), valid);
+            }
+        }
+    }
+    if lenient && format != Format::Json {
+        println!("Skipped: {}", skipped.len());
+    }
+    Ok(())
 }
 
 pub fn main() {
-    let mut args = std::env::args();
-    let path = args.nth(1)
-        .expect("Please provide path to the input file");
-    let file = File::open(path).unwrap();
-    let mut valid = 0;
-    let lines = BufReader::new(file).lines();
-    for line in lines {
-        let line = line.unwrap();
-        let (policy, password) = password_policy_parser::root(&line).unwrap();
-        // let occurences = password.chars().filter(|&c| c == policy.c).count();
-        // if policy.range.contains(&occurences) {
-        //     valid += 1;
-        // }
-        let occurences = password.chars()
-            .enumerate()
-            .filter(|&(i, c)| {
-                (i == policy.range.start() - 1 || i == policy.range.end() - 1) &&
-                    c == policy.c
-            })
-            .count();
-        if occurences == 1 {
-            valid += 1;
-        }
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let format = match Format::take(&mut args) {
+        Ok(Some(Format::Tsv)) => Error::usage("Only text and json formats are supported").exit(USAGE, Format::Text),
+        Ok(format) => format.unwrap_or(Format::Text),
+        Err(e) => e.exit(USAGE, Format::Text)
+    };
+    if let Err(e) = run(args, format) {
+        e.exit(USAGE, format)
     }
-    println!("Valid: {}", valid);
 }
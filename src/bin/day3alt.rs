@@ -1,204 +1,69 @@
-use std::{
-    convert::{TryFrom, TryInto},
-    fmt::{Display, Formatter},
-    str::FromStr
+use xmascode::{Error, Format, ParseError, Source};
+use xmascode::day3::{
+    self,
+    SLOPES,
+    dense::{Grid, Tile}
 };
+use xmascode::json::Json;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Tile {
-    Open,
-    Tree
-}
+const USAGE: &str = "\
+Usage: day3alt [--format text|json] [INPUT]
 
-impl Default for Tile {
-    fn default() -> Self {
-        Tile::Open
-    }
-}
+Reads the map from INPUT, or from stdin if it's `-` or missing.";
 
-impl Display for Tile {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Tile::Open => write!(f, "."),
-            Tile::Tree => write!(f, "#")
-        }
-    }
-}
-
-#[derive(Debug)]
-struct ParseError(String);
+fn run(args: Vec<String>, format: Format) -> Result<(), Error> {
 
-impl ParseError {
-    pub fn new(s: impl Into<String>) -> Self {
-        ParseError(s.into())
-    }
-}
+    let mut args = args.into_iter();
+    let source = Source::from_arg(args.next());
+    let input = source.read_to_string()?;
 
-impl TryFrom<char> for Tile {
-    type Error = ParseError;
+    let grid: Grid = str::parse(&input).map_err(|e: ParseError| e.in_file(source.name()))?;
 
-    fn try_from(c: char) -> Result<Self, Self::Error> {
-        match c {
-            '.' => Ok(Tile::Open),
-            '#' => Ok(Tile::Tree),
-            _ => Err(ParseError::new(format!("Invalid tile: {}", c)))
-        }
-    }
-}
-
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct Coord {
-    x: usize,
-    y: usize
-}
-
-impl From<(usize, usize)> for Coord {
-    fn from((x, y): (usize, usize)) -> Self {
-        Self { x, y }
-    }
-}
-
-#[derive(Debug)]
-struct Grid {
-    size: Coord,
-    tiles: Vec<Tile>
-}
-
-impl Grid {
-
-    pub fn new(size: impl Into<Coord>) -> Grid {
-        let size = size.into();
-        Grid {
-            size,
-            tiles: vec![Tile::default(); size.x * size.y]
-        }
-    }
-
-    pub fn get(&self, coord: impl Into<Coord>) -> Tile {
-        self.tiles[self.index(coord.into())]
-    }
-
-    pub fn set(&mut self, coord: impl Into<Coord>, tile: Tile) {
-        let index = self.index(coord.into());
-        self.tiles[index] = tile
-    }
-
-    pub fn route(&self, step: impl Into<Coord>) -> impl Iterator<Item = Tile> + '_ {
-        Route::new(self.size, step.into()).map(move |coord| self.get(coord))
-    }
-
-    fn index(&self, coord: Coord) -> usize {
-        assert!(coord.y < self.size.y);
-        coord.y * self.size.x + coord.x % self.size.x
-    }
-
-}
+    let routes = SLOPES.iter().map(|&slope| grid.route(slope));
 
-impl Display for Grid {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for y in 0..self.size.y {
-            for x in 0..self.size.x {
-                self.get((x, y)).fmt(f)?
-            }
-            write!(f, "\n")?
-        }
-        Ok(())
-    }
-}
-
-impl FromStr for Grid {
-
-    type Err = ParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-
-        let height = s.lines().count();
-        let mut width = None;
-        for line in s.lines() {
-            let w = line.len();
-            match width {
-                Some(w0) => {
-                    if w0 != w {
-                        return Err(ParseError::new(format!("Irregular input: rows has lengths: {} vs {}", w0, w)))
-                    }
-                }
-                None => {
-                    width = Some(w)
-                }
+    let hits: Vec<usize> = routes.map(|route| {
+        route.filter(|&tile| tile == Tile::Tree).count()
+    }).collect();
+
+    let product = day3::product(&hits);
+
+    match format {
+        Format::Json => {
+            println!("{}", Json::object()
+                .with("day", 3)
+                .with("part", 2)
+                .with("input", source.name())
+                .with("answer", product)
+                .with("slopes", SLOPES.to_vec())
+                .with("hits", hits.clone()));
+        },
+        _ => {
+            println!("{}", grid);
+            for hs in &hits {
+                println!("Hits: {}", hs);
             }
-        }
-
-        let width = width.unwrap_or(0);
-        let mut grid = Grid::new((width, height));
-        for (y, line) in s.lines().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                let tile: Tile = c.try_into()?;
-                grid.set((x, y), tile)
+            match product {
+                Some(product) => println!("Product: {}", product),
+                None => println!("Product overflows")
             }
         }
-
-        Ok(grid)
-    }
-
-}
-
-#[derive(Debug)]
-struct Route {
-    size: Coord,
-    coord: Coord,
-    step: Coord
-}
-
-impl Route {
-    pub fn new(size: Coord, step: Coord) -> Self {
-        Self { size, coord: (0, 0).into(), step }
     }
-}
-
-impl Iterator for Route {
 
-    type Item = Coord;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.coord.y < self.size.y {
-            let next = Some(self.coord);
-            self.coord.x += self.step.x;
-            self.coord.y += self.step.y;
-            next
-        }
-        else {
-            None
-        }
+    match product {
+        Some(_) => Ok(()),
+        None => Err(Error::Overflow(format!("product of {:?} does not fit into 64 bits", hits)))
     }
 
 }
 
 pub fn main() {
-
-    let mut args = std::env::args();
-    let path = args.nth(1).expect("Please provide path to the input file");
-    let input = std::fs::read_to_string(path).unwrap();
-
-    let grid: Grid = str::parse(&input).unwrap();
-    println!("{}", grid);
-
-    let routes = vec![
-        grid.route((1, 1)),
-        grid.route((3, 1)),
-        grid.route((5, 1)),
-        grid.route((7, 1)),
-        grid.route((1, 2))
-    ];
-
-    let hits = routes.into_iter().map(|route| {
-        route.filter(|&tile| tile == Tile::Tree).count()
-    });
-
-    let product = hits.fold(1, |acc, hs| {
-        println!("Hits: {}", hs);
-        acc * hs
-    });
-
-    println!("Product: {}", product);
-
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let format = match Format::take(&mut args) {
+        Ok(Some(Format::Tsv)) => Error::usage("Only text and json formats are supported").exit(USAGE, Format::Text),
+        Ok(format) => format.unwrap_or(Format::Text),
+        Err(e) => e.exit(USAGE, Format::Text)
+    };
+    if let Err(e) = run(args, format) {
+        e.exit(USAGE, format)
+    }
 }
@@ -0,0 +1,404 @@
+use xmascode::{Day, Error, Format, Part, Source, DAYS};
+use xmascode::json::Json;
+use xmascode::metrics::{self, Snapshot};
+use xmascode::answers::{self, Manifest};
+use xmascode::generate::{self, Rng};
+use xmascode::timing::{self, Report};
+
+const USAGE: &str = "\
+Usage:
+    xmascode run <DAY|all> [--part 1|2] [--solver NAME] [--time] [--repeat N] [--metrics] [--format text|tsv|json] [INPUT]
+        INPUT defaults to input/dayN, `-` reads stdin.
+    xmascode verify [MANIFEST]
+    xmascode generate <DAY> [--seed N] [--size N] [OPTIONS]
+        day 1: [--k K] [--target T] [--max M]
+        day 2: [--valid-ratio R] [--rule occurrences|positions]
+        day 3: [--width W] [--height H] [--density D]
+    xmascode list
+
+Exit codes:
+    1   verification found mismatching answers
+    2   there is no solution for some part
+    3   the answer does not fit into an integer
+    64  invalid command line
+    65  malformed input
+    66  input file does not exist
+    74  input file could not be read";
+
+#[derive(Debug)]
+struct RunArgs {
+    day: Option<u8>,
+    parts: Vec<Part>,
+    solver: Option<String>,
+    time: bool,
+    repeat: usize,
+    metrics: bool,
+    format: Format,
+    input: Option<String>
+}
+
+impl Default for RunArgs {
+    fn default() -> Self {
+        RunArgs {
+            day: None,
+            parts: Vec::new(),
+            solver: None,
+            time: false,
+            repeat: 1,
+            metrics: false,
+            format: Format::Text,
+            input: None
+        }
+    }
+}
+
+fn parse_run_args(mut args: impl Iterator<Item = String>, format: Format) -> Result<RunArgs, Error> {
+    let mut run = RunArgs { format, ..RunArgs::default() };
+    match args.next().as_deref() {
+        Some("all") => {},
+        Some(day) => run.day = Some(day.parse().map_err(|_| Error::usage(format!("Invalid day: {}", day)))?),
+        None => return Err(Error::usage("Please provide the day to run"))
+    }
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--part" => match args.next().as_deref() {
+                Some("1") => run.parts.push(Part::One),
+                Some("2") => run.parts.push(Part::Two),
+                other => return Err(Error::usage(format!("Invalid part: {:?}", other)))
+            },
+            "--solver" => {
+                run.solver = Some(args.next().ok_or_else(|| Error::usage("Please provide solver name"))?)
+            },
+            "--time" => run.time = true,
+            "--metrics" => run.metrics = true,
+            "--repeat" => {
+                run.time = true;
+                run.repeat = match args.next().map(|n| n.parse()) {
+                    Some(Ok(n)) if n > 0 => n,
+                    _ => return Err(Error::usage("Repeat count must be a positive integer"))
+                }
+            },
+            _ if arg.starts_with("--") => return Err(Error::usage(format!("Unknown option: {}", arg))),
+            _ if run.input.is_none() => run.input = Some(arg),
+            _ => return Err(Error::usage(format!("Unexpected argument: {}", arg)))
+        }
+    }
+    if run.parts.is_empty() {
+        run.parts = Part::ALL.to_vec();
+    }
+    Ok(run)
+}
+
+fn print_timings(day: &Day, report: &Report, format: Format) {
+    match format {
+        Format::Json => {},
+        Format::Text => {
+            println!("Day {} ({}) timings over {} runs:", day.day, day.name, report.repeat);
+            for (phase, samples) in &report.phases {
+                println!(
+                    "    {:<6} min {:>10}  median {:>10}  max {:>10}",
+                    phase.to_string(),
+                    timing::human(samples.min()),
+                    timing::human(samples.median()),
+                    timing::human(samples.max())
+                );
+            }
+        },
+        Format::Tsv => {
+            for (phase, samples) in &report.phases {
+                println!(
+                    "time\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                    day.day, day.name, phase, report.repeat,
+                    samples.min(), samples.median(), samples.max()
+                );
+            }
+        }
+    }
+}
+
+fn print_metrics(day: &Day, part: Part, metrics: &Snapshot, format: Format) {
+    for (name, value) in metrics.values() {
+        match format {
+            Format::Tsv => println!("metric\t{}\t{}\t{}\t{}\t{}", day.day, day.name, part, name, value),
+            _ => println!("    {}: {}", name, value)
+        }
+    }
+}
+
+/// Returns parts which turned out to have no answer, along with whether that's
+/// because the answer overflowed.
+fn run_day(day: &Day, args: &RunArgs, source: &Source) -> Result<Vec<(Part, bool)>, Error> {
+    let input = source.read_to_string()?;
+    let path = source.name();
+    metrics::reset();
+    let solution = day.parse(&input).map_err(|e| e.in_file(path))?;
+    let parsed = Snapshot::take();
+    let report = match args.time {
+        true => Some(timing::time(day, &input, &args.parts, args.repeat)?.1),
+        false => None
+    };
+    let mut unsolved = Vec::new();
+    for &part in &args.parts {
+        metrics::reset();
+        let outcome = solution.solve(part);
+        let metrics = parsed.merge(&Snapshot::take());
+        if outcome.answer.is_none() {
+            unsolved.push((part, outcome.overflow));
+        }
+        if args.format == Format::Json {
+            let json = Json::object()
+                .with("day", day.day)
+                .with("solver", day.name)
+                .with("input", path)
+                .with("part", part)
+                .with("answer", outcome.answer)
+                .with("overflow", outcome.overflow)
+                .with("details", outcome.details_json())
+                .with("metrics", metrics.to_json());
+            match &report {
+                Some(report) => println!("{}", json.with("time", report.to_json())),
+                None => println!("{}", json)
+            }
+            continue
+        }
+        match (args.format, outcome.answer) {
+            (Format::Text, Some(answer)) => println!("Day {} ({}) part {}: {}", day.day, day.name, part, answer),
+            (Format::Text, None) if outcome.overflow => println!("Day {} ({}) part {}: overflow", day.day, day.name, part),
+            (Format::Text, None) => println!("Day {} ({}) part {}: no solution", day.day, day.name, part),
+            (_, Some(answer)) => println!("answer\t{}\t{}\t{}\t{}", day.day, day.name, part, answer),
+            (_, None) => println!("answer\t{}\t{}\t{}\t", day.day, day.name, part)
+        }
+        if args.metrics {
+            print_metrics(day, part, &metrics, args.format);
+        }
+    }
+    if let Some(report) = report {
+        print_timings(day, &report, args.format);
+    }
+    Ok(unsolved)
+}
+
+fn run(args: RunArgs) -> Result<(), Error> {
+    let days: Vec<&Day> = match (args.day, &args.solver) {
+        (Some(n), Some(name)) => vec![
+            xmascode::solver(n, name).ok_or_else(|| Error::usage(format!("No solver {} for day {}", name, n)))?
+        ],
+        (Some(n), None) => vec![
+            xmascode::day(n).ok_or_else(|| Error::usage(format!("No solver for day {}", n)))?
+        ],
+        (None, Some(_)) => return Err(Error::usage("Solver can only be picked for a specific day")),
+        (None, None) => {
+            let mut days: Vec<&Day> = DAYS.iter().collect();
+            days.dedup_by_key(|d| d.day);
+            days
+        }
+    };
+    if days.len() > 1 && args.input.is_some() {
+        return Err(Error::usage("Input can only be provided for a specific day"));
+    }
+    let mut unsolved = Vec::new();
+    let mut overflowed = Vec::new();
+    for day in days {
+        let input = args.input.clone().unwrap_or_else(|| day.input());
+        for (part, overflow) in run_day(day, &args, &Source::from_arg(Some(input)))? {
+            let which = format!("day {} part {}", day.day, part);
+            match overflow {
+                true => overflowed.push(which),
+                false => unsolved.push(which)
+            }
+        }
+    }
+    if !overflowed.is_empty() {
+        return Err(Error::Overflow(format!("answers to {} do not fit into 64 bits", overflowed.join(", "))));
+    }
+    match unsolved.is_empty() {
+        true => Ok(()),
+        false => Err(Error::NoSolution(unsolved.join(", ")))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Tally {
+    passed: usize,
+    failed: usize,
+    missing: usize,
+    overflowed: usize
+}
+
+fn verify_input(day: &Day, manifest: &Manifest, input: &str, tally: &mut Tally) {
+    let solution = std::fs::read_to_string(input)
+        .map_err(|e| e.to_string())
+        .and_then(|text| day.parse(&text).map_err(|e| e.in_file(input).to_string()));
+    for part in Part::ALL.iter().copied() {
+        let expected = manifest.lookup(day.day, part, input);
+        let prefix = format!("day {} ({}) part {} {}", day.day, day.name, part, input);
+        let solution = match &solution {
+            Ok(solution) => solution,
+            Err(reason) => {
+                tally.failed += 1;
+                println!("FAIL    {}: {}", prefix, reason);
+                continue
+            }
+        };
+        let outcome = solution.solve(part);
+        if outcome.overflow {
+            tally.overflowed += 1;
+            match expected {
+                Some(expected) => println!("FAIL    {}: overflow, expected {}", prefix, expected),
+                None => println!("MISSING {}: overflow", prefix)
+            }
+            continue
+        }
+        match (outcome.answer, expected) {
+            (Some(answer), Some(expected)) if answer == expected => {
+                tally.passed += 1;
+                println!("PASS    {}: {}", prefix, answer);
+            },
+            (Some(answer), Some(expected)) => {
+                tally.failed += 1;
+                println!("FAIL    {}: {}, expected {}", prefix, answer, expected);
+            },
+            (None, Some(expected)) => {
+                tally.failed += 1;
+                println!("FAIL    {}: no solution, expected {}", prefix, expected);
+            },
+            (Some(answer), None) => {
+                tally.missing += 1;
+                println!("MISSING {}: {}", prefix, answer);
+            },
+            (None, None) => {
+                tally.missing += 1;
+                println!("MISSING {}: no solution", prefix);
+            }
+        }
+    }
+}
+
+fn verify(mut args: impl Iterator<Item = String>) -> Result<(), Error> {
+    let path = args.next().unwrap_or_else(|| answers::MANIFEST.to_string());
+    if let Some(arg) = args.next() {
+        return Err(Error::usage(format!("Unexpected argument: {}", arg)));
+    }
+    let manifest: Manifest = Source::File(path.clone()).read_to_string()?
+        .parse()
+        .map_err(|e: xmascode::ParseError| e.in_file(path.as_str()))?;
+    let mut tally = Tally::default();
+    for day in DAYS {
+        let inputs = manifest.inputs(day.day);
+        if inputs.is_empty() {
+            tally.missing += 1;
+            println!("MISSING day {} ({}): no answers in {}", day.day, day.name, path);
+        }
+        for input in inputs {
+            verify_input(day, &manifest, input, &mut tally);
+        }
+    }
+    println!(
+        "Passed: {}, failed: {}, missing: {}, overflowed: {}",
+        tally.passed, tally.failed, tally.missing, tally.overflowed
+    );
+    match (tally.failed, tally.overflowed) {
+        (0, 0) => Ok(()),
+        (0, n) => Err(Error::Overflow(format!("{} answers do not fit into 64 bits", n))),
+        (n, _) => Err(Error::Mismatch(format!("{} answers do not match {}", n, path)))
+    }
+}
+
+fn option<T: std::str::FromStr>(options: &[(String, String)], name: &str, default: T) -> Result<T, Error> {
+    match options.iter().rev().find(|(n, _)| n == name) {
+        Some((_, value)) => value.parse().map_err(|_| Error::usage(format!("Invalid {}: {}", name, value))),
+        None => Ok(default)
+    }
+}
+
+fn generate(mut args: impl Iterator<Item = String>) -> Result<(), Error> {
+    let day: u8 = args.next()
+        .ok_or_else(|| Error::usage("Please provide the day to generate input for"))?
+        .parse()
+        .map_err(|_| Error::usage("Day must be a number"))?;
+    let mut options = Vec::new();
+    while let Some(name) = args.next() {
+        let value = args.next().ok_or_else(|| Error::usage(format!("Please provide value for {}", name)))?;
+        match name.strip_prefix("--") {
+            Some(name) => options.push((name.to_string(), value)),
+            None => return Err(Error::usage(format!("Unexpected argument: {}", name)))
+        }
+    }
+    let known: &[&str] = match day {
+        1 => &["seed", "size", "k", "target", "max"],
+        2 => &["seed", "size", "valid-ratio", "rule"],
+        3 => &["seed", "size", "width", "height", "density"],
+        _ => return Err(Error::usage(format!("No generator for day {}", day)))
+    };
+    if let Some((name, _)) = options.iter().find(|(name, _)| !known.contains(&name.as_str())) {
+        return Err(Error::usage(format!("Unknown option for day {}: --{}", day, name)));
+    }
+    let mut rng = Rng::new(option(&options, "seed", 2020)?);
+    let stdout = std::io::stdout();
+    let mut out = std::io::BufWriter::new(stdout.lock());
+    let written = match day {
+        1 => {
+            let size = option(&options, "size", 200)?;
+            let generator = generate::Expenses {
+                count: size,
+                k: option(&options, "k", 3)?,
+                target: option(&options, "target", xmascode::day1::TARGET)?,
+                max: option(&options, "max", (size as i64 * 4).max(2020))?
+            };
+            generator.check().map_err(Error::Usage)?;
+            generator.write(&mut rng, &mut out)
+        },
+        2 => {
+            let rule = match option(&options, "rule", "positions".to_string())?.as_str() {
+                "occurrences" => generate::Rule::Occurrences,
+                "positions" => generate::Rule::Positions,
+                other => return Err(Error::usage(format!("Invalid rule: {}", other)))
+            };
+            let generator = generate::Passwords {
+                count: option(&options, "size", 1000)?,
+                valid_ratio: option(&options, "valid-ratio", 0.5)?,
+                rule
+            };
+            generator.check().map_err(Error::Usage)?;
+            generator.write(&mut rng, &mut out)
+        },
+        _ => {
+            let generator = generate::Forest {
+                width: option(&options, "width", 31)?,
+                height: option(&options, "height", option(&options, "size", 323)?)?,
+                density: option(&options, "density", 0.2)?
+            };
+            generator.check().map_err(Error::Usage)?;
+            generator.write(&mut rng, &mut out)
+        }
+    };
+    written
+        .and_then(|_| std::io::Write::flush(&mut out))
+        .map_err(|e| Error::io("<stdout>", e))
+}
+
+fn list() -> Result<(), Error> {
+    for day in DAYS {
+        println!("{}\t{}\t{}", day.day, day.name, day.input());
+    }
+    Ok(())
+}
+
+pub fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let format = Format::take(&mut args)
+        .unwrap_or_else(|e| e.exit(USAGE, Format::Text))
+        .unwrap_or(Format::Text);
+    let mut args = args.into_iter();
+    let result = match args.next().as_deref() {
+        Some("run") => parse_run_args(args, format).and_then(run),
+        Some("verify") => verify(args),
+        Some("generate") => generate(args),
+        Some("list") => list(),
+        Some(command) => Err(Error::usage(format!("Unknown command: {}", command))),
+        None => Err(Error::usage("Please provide a command"))
+    };
+    if let Err(e) = result {
+        e.exit(USAGE, format)
+    }
+}
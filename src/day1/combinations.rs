@@ -0,0 +1,188 @@
+//! Lazy enumeration of every combination summing up to the target.
+//!
+//! Same two-pointer scan at the bottom and heads fixed one by one above it as
+//! in [`SetIterator::find_n_sum`], only with the recursion unrolled into an
+//! explicit stack of levels, so that the search can be suspended after each
+//! hit and resumed on the next call.
+
+use std::cmp::Ordering;
+
+use super::{HEADS, ITERATIONS, Integer, SetIterator, find_one};
+
+/// Pair search over what's left of the values, the bottom of the recursion.
+#[derive(Debug, Clone)]
+pub(super) struct Pair<It, I> {
+    iter: It,
+    head: Option<I>,
+    tail: Option<I>,
+    target: I
+}
+
+impl<It, I> Pair<It, I> where
+    It: DoubleEndedIterator<Item = I>,
+    I: Integer
+{
+
+    pub(super) fn new(mut iter: It, target: I) -> Self {
+        let head = iter.next();
+        let tail = iter.next_back();
+        Pair { iter, head, tail, target }
+    }
+
+    /// Moves on from the pair the scan is at, telling how its sum compares
+    /// to the target.
+    pub(super) fn step(&mut self) -> Option<(I, I, Ordering)> {
+        let (h, t) = (self.head?, self.tail?);
+        ITERATIONS.inc();
+        let sum = h.cmp_sum(t, self.target);
+        match sum {
+            Ordering::Equal => {
+                // Step over repeated values on both ends, they'd make the same pair.
+                self.head = self.iter.by_ref().find(|&v| v != h);
+                self.tail = match self.head {
+                    Some(_) => self.iter.by_ref().rev().find(|&v| v != t),
+                    None => None
+                };
+            },
+            Ordering::Less => self.head = self.iter.next(),
+            Ordering::Greater => self.tail = self.iter.next_back()
+        }
+        Some((h, t, sum))
+    }
+
+    fn next_hit(&mut self) -> Option<(I, I)> {
+        while let Some((h, t, sum)) = self.step() {
+            if sum == Ordering::Equal {
+                return Some((h, t));
+            }
+        }
+        None
+    }
+
+}
+
+/// Head fixed at some level of the recursion, along with the values left to
+/// pick the rest of the combination from.
+#[derive(Debug, Clone)]
+struct Level<It, I> {
+    iter: It,
+    target: I,
+    head: Option<I>
+}
+
+impl<It, I> Level<It, I> where
+    It: DoubleEndedIterator<Item = I>,
+    I: Integer
+{
+
+    fn next_head(&mut self) -> Option<I> {
+        loop {
+            let h = self.iter.next()?;
+            if Some(h) != self.head {
+                HEADS.inc();
+                self.head = Some(h);
+                return Some(h);
+            }
+        }
+    }
+
+}
+
+/// Iterator over distinct combinations of `n` values summing up to the
+/// target, created with [`SetIterator::n_sums`]. Combinations come in the
+/// same shape [`SetIterator::find_n_sum`] returns them in.
+#[derive(Debug, Clone)]
+pub struct NSums<It, I> {
+    n: u8,
+    levels: Vec<Level<It, I>>,
+    pair: Option<Pair<It, I>>
+}
+
+impl<It, I> NSums<It, I> where
+    It: DoubleEndedIterator<Item = I> + Clone,
+    I: Integer
+{
+
+    pub(super) fn new(iter: It, n: u8, target: I) -> Self {
+        match n {
+            0 => NSums { n, levels: Vec::new(), pair: None },
+            2 => NSums { n, levels: Vec::new(), pair: Some(Pair::new(iter, target)) },
+            _ => NSums { n, levels: vec![Level { iter, target, head: None }], pair: None }
+        }
+    }
+
+}
+
+impl<It, I> Iterator for NSums<It, I> where
+    It: DoubleEndedIterator<Item = I> + Clone,
+    I: Integer
+{
+    type Item = Vec<I>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.n == 1 {
+            let level = self.levels.pop()?;
+            return find_one(level.iter, level.target).map(|v| vec![v]);
+        }
+        loop {
+            if let Some(pair) = &mut self.pair {
+                if let Some((h, t)) = pair.next_hit() {
+                    let mut found = vec![h, t];
+                    found.extend(self.levels.iter().rev().filter_map(|level| level.head));
+                    return Some(found);
+                }
+                self.pair = None;
+            }
+            let depth = self.levels.len();
+            let level = self.levels.last_mut()?;
+            match level.next_head() {
+                Some(h) => {
+                    let target = match level.target.checked_sub(h) {
+                        Some(target) => target,
+                        None => continue
+                    };
+                    let iter = level.iter.clone();
+                    if self.n as usize - depth == 2 {
+                        self.pair = Some(Pair::new(iter, target));
+                    }
+                    else {
+                        self.levels.push(Level { iter, target, head: None });
+                    }
+                },
+                None => {
+                    self.levels.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Counts what [`NSums`] would yield, without materializing anything.
+pub(super) fn count_n_sum<It, I>(mut iter: It, n: u8, target: I) -> usize where
+    It: DoubleEndedIterator<Item = I> + Clone,
+    I: Integer
+{
+    match n {
+        0 => 0,
+        1 => find_one(iter, target).is_some() as usize,
+        2 => {
+            let mut pair = Pair::new(iter, target);
+            std::iter::from_fn(|| pair.next_hit()).count()
+        },
+        _ => {
+            let mut count = 0;
+            let mut last = None;
+            while let Some(h) = iter.next() {
+                if Some(h) == last {
+                    continue;
+                }
+                HEADS.inc();
+                last = Some(h);
+                if let Some(rest) = target.checked_sub(h) {
+                    count += iter.clone().count_n_sum(n - 1, rest);
+                }
+            }
+            count
+        }
+    }
+}
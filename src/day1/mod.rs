@@ -0,0 +1,510 @@
+//! Day 1: Report Repair, looking for expenses summing up to a target.
+
+use std::cmp::Ordering;
+use std::hash::Hash;
+use std::iter::Copied;
+use std::num::{IntErrorKind, ParseIntError};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use crate::{Answer, Error, Outcome, ParseError, Solution};
+use crate::metrics::Metric;
+
+mod combinations;
+mod nearest;
+mod online;
+mod parallel;
+mod range;
+mod strategy;
+mod subset;
+mod trace;
+mod window;
+
+pub use combinations::NSums;
+pub use nearest::Nearest;
+pub use online::Detector;
+pub use range::RangeSums;
+pub use strategy::Strategy;
+pub use subset::SubsetSums;
+pub use trace::{Event, Trace, Tracer};
+pub use window::{Run, find_run};
+
+pub type Value = i64;
+
+/// Sum every expense pair or triple in the report should add up to.
+pub const TARGET: Value = 2020;
+
+/// Integer type values and targets can be of. Arithmetic is checked, so
+/// that searches can tell when a sum or a partial target does not fit into
+/// the type, e.g. `target - h` with `h > target` for unsigned types.
+pub trait Integer: Ord + Eq + Hash + Copy + Default + std::fmt::Debug {
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+
+    /// Compares `self + rhs` against `target` as if there was no overflow.
+    fn cmp_sum(self, rhs: Self, target: Self) -> Ordering {
+        match self.checked_add(rhs) {
+            Some(sum) => sum.cmp(&target),
+            // Adding only overflows past the maximum with both positive, and
+            // past the minimum with both negative.
+            None if rhs > Self::default() => Ordering::Greater,
+            None => Ordering::Less
+        }
+    }
+
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {$(
+        impl Integer for $t {
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+
+        }
+    )*};
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Product of the values found, `None` if it overflows or there's nothing to
+/// multiply.
+pub fn product<I: Integer>(found: &[I]) -> Option<I> {
+    let (&first, rest) = found.split_first()?;
+    rest.iter().try_fold(first, |product, &v| product.checked_mul(v))
+}
+
+/// Steps taken by two-pointer scans, each one a comparison against the target.
+pub static ITERATIONS: Metric = Metric::counter("day1.iterations");
+/// Candidates fixed as the head of a combination before recursing.
+pub static HEADS: Metric = Metric::counter("day1.heads");
+/// Probes of hash sets and maps for the complement of a partial sum.
+pub static LOOKUPS: Metric = Metric::counter("day1.lookups");
+/// Half-size combinations a meet-in-the-middle search went through.
+pub static PARTIALS: Metric = Metric::counter("day1.partials");
+/// Words of subset sum bitsets shifted into the next row.
+pub static SHIFTS: Metric = Metric::counter("day1.shifts");
+/// Threads the last parallel search was split across.
+pub static WORKERS: Metric = Metric::gauge("day1.workers");
+/// Expenses in the report, duplicates included.
+pub static EXPENSES: Metric = Metric::gauge("day1.expenses");
+
+/// Every day 1 metric, for [`crate::metrics`] to reset and report.
+pub static METRICS: &[&Metric] = &[
+    &ITERATIONS,
+    &HEADS,
+    &LOOKUPS,
+    &PARTIALS,
+    &SHIFTS,
+    &WORKERS,
+    &EXPENSES
+];
+
+/// Searches over an iterator yielding values in ascending order. Repeated
+/// values are fine, each one is usable as many times as it's repeated.
+pub trait SetIterator<I: Integer>: DoubleEndedIterator<Item = I> {
+
+    /// Finds `n` values summing up to `target`. The pair the two-pointer scan
+    /// at the bottom settled on comes first, smaller one first, followed by
+    /// the heads fixed above it, innermost to outermost. For `n` of 1 that's
+    /// the target itself, if it's there. Nothing ever sums up to anything with
+    /// `n` of 0. Heads leaving a partial target that does not fit into the
+    /// type are skipped.
+    fn find_n_sum(self, n: u8, target: I) -> Option<Vec<I>> where
+        Self: Sized + Clone
+    {
+        let mut scan = Scan::new(());
+        let found = search(self, n, target, 0, &mut scan);
+        scan.flush();
+        found
+    }
+
+    /// Same as [`SetIterator::find_n_sum`], telling `tracer` about every step
+    /// the search takes.
+    fn find_n_sum_traced<T: Tracer<I>>(self, n: u8, target: I, tracer: &mut T) -> Option<Vec<I>> where
+        Self: Sized + Clone
+    {
+        let mut scan = Scan::new(tracer);
+        let found = search(self, n, target, 0, &mut scan);
+        scan.flush();
+        found
+    }
+
+    /// Same as [`SetIterator::find_n_sum`], only searching the way `strategy`
+    /// says. Order of the values found depends on the strategy.
+    fn find_n_sum_by(self, strategy: Strategy, n: u8, target: I) -> Option<Vec<I>> where
+        Self: Sized + Clone
+    {
+        strategy::find_n_sum(strategy, self, n, target)
+    }
+
+    /// Same as [`SetIterator::find_n_sum`], with the outermost heads split
+    /// across up to `threads` scoped threads. Finds the very same values the
+    /// sequential search does. Searches for fewer than 3 values, or with
+    /// fewer than 2 threads, are done sequentially.
+    fn par_find_n_sum(self, n: u8, target: I, threads: usize) -> Option<Vec<I>> where
+        Self: Sized + Clone,
+        I: Send + Sync
+    {
+        parallel::find_n_sum(&self.collect::<Vec<_>>(), n, target, threads)
+    }
+
+    /// Finds `n` values summing up as close to `target` as there are, or as
+    /// close without going over it if `at_most` is set. Ties go to whichever
+    /// combination the scan comes across first.
+    fn nearest_n_sum(self, n: u8, target: I, at_most: bool) -> Option<Nearest<I>> where
+        Self: Sized + Clone
+    {
+        nearest::nearest_n_sum(self, n, target, at_most)
+    }
+
+    /// Lazily enumerates every distinct combination of `n` values summing up
+    /// to `target`. Combinations differing only in which of the repeated
+    /// values they picked count as one.
+    fn n_sums(self, n: u8, target: I) -> NSums<Self, I> where
+        Self: Sized + Clone
+    {
+        NSums::new(self, n, target)
+    }
+
+    /// Counts combinations [`SetIterator::n_sums`] would enumerate.
+    fn count_n_sum(self, n: u8, target: I) -> usize where
+        Self: Sized + Clone
+    {
+        combinations::count_n_sum(self, n, target)
+    }
+
+    /// Lazily enumerates every distinct combination of `n` values summing up
+    /// to anything within `range`. Same as with [`SetIterator::find_n_sum`],
+    /// heads leaving a range entirely out of the type are skipped.
+    fn range_sums(self, n: u8, range: RangeInclusive<I>) -> RangeSums<Self, I> where
+        Self: Sized + Clone
+    {
+        RangeSums::new(self, n, range)
+    }
+
+    /// Counts combinations [`SetIterator::range_sums`] would enumerate.
+    fn count_range_sums(self, n: u8, range: RangeInclusive<I>) -> usize where
+        Self: Sized + Clone
+    {
+        range::count_range_sums(self, n, range)
+    }
+
+}
+
+impl<T, I: Integer> SetIterator<I> for T where T: DoubleEndedIterator<Item = I> {}
+
+/// Bookkeeping of a single search, possibly spanning several calls to
+/// [`search`]. Work is counted here and only added to the metrics once the
+/// search is over, so that parallel workers don't fight over them.
+pub(super) struct Scan<'a, T> {
+    tracer: T,
+    /// Index of the best head found by any parallel worker so far, along with
+    /// the index of the head this search runs under. The search gives up as
+    /// soon as it can't do better anymore.
+    pub(super) cutoff: Option<(&'a AtomicUsize, usize)>,
+    iterations: usize,
+    pub(super) heads: usize
+}
+
+impl<T> Scan<'_, T> {
+
+    pub(super) fn new(tracer: T) -> Self {
+        Scan { tracer, cutoff: None, iterations: 0, heads: 0 }
+    }
+
+    fn cut(&self) -> bool {
+        self.cutoff.is_some_and(|(best, idx)| best.load(AtomicOrdering::Relaxed) < idx)
+    }
+
+    pub(super) fn flush(&self) {
+        ITERATIONS.add(self.iterations);
+        HEADS.add(self.heads);
+    }
+
+}
+
+/// Engine behind [`SetIterator::find_n_sum`], `depth` levels deep into the
+/// recursion. Tracing compiles away with the `()` tracer. Gives up before
+/// each head if the scan is cut off.
+pub(super) fn search<It, I, T>(mut iter: It, n: u8, target: I, depth: usize, scan: &mut Scan<'_, T>) -> Option<Vec<I>> where
+    It: DoubleEndedIterator<Item = I> + Clone,
+    I: Integer,
+    T: Tracer<I>
+{
+    if T::ENABLED {
+        scan.tracer.event(Event::Search { depth, n, target });
+    }
+    let found = match n {
+        0     => None,
+        1     => find_one(iter, target).map(|v| vec![v]),
+        2     => {
+            let mut head = iter.next();
+            let mut tail = iter.next_back();
+            let mut found = None;
+            while let (Some(h), Some(t)) = (head, tail) {
+                scan.iterations += 1;
+                let sum = h.cmp_sum(t, target);
+                if T::ENABLED {
+                    scan.tracer.event(Event::Step { depth, head: h, tail: t, target, sum });
+                }
+                match sum {
+                    Ordering::Equal => {
+                        found = Some(vec![h, t]);
+                        break;
+                    },
+                    Ordering::Less => head = iter.next(),
+                    Ordering::Greater => tail = iter.next_back()
+                }
+            }
+            found
+        },
+        _     => {
+            let mut found = None;
+            while let Some(h) = iter.next() {
+                if scan.cut() {
+                    break;
+                }
+                scan.heads += 1;
+                let rest = target.checked_sub(h);
+                if T::ENABLED {
+                    scan.tracer.event(Event::Head { depth, head: h, rest });
+                }
+                let rest = match rest {
+                    Some(rest) => rest,
+                    None => continue
+                };
+                if let Some(mut values) = search(iter.clone(), n - 1, rest, depth + 1, scan) {
+                    values.push(h);
+                    found = Some(values);
+                    break;
+                }
+            }
+            found
+        }
+    };
+    if T::ENABLED {
+        scan.tracer.event(Event::Done { depth, found: found.clone() });
+    }
+    found
+}
+
+/// Scans for the `target` itself, stopping as soon as values exceed it.
+fn find_one<I: Integer>(iter: impl Iterator<Item = I>, target: I) -> Option<I> {
+    for v in iter {
+        ITERATIONS.inc();
+        if v >= target {
+            return Some(v).filter(|&v| v == target);
+        }
+    }
+    None
+}
+
+pub fn iterations() -> usize {
+    ITERATIONS.get() as usize
+}
+
+pub fn parse_expense(line: &str) -> Result<Value, ParseError> {
+    line.parse().map_err(|e: ParseIntError| match e.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+            ParseError::new("Expense out of range").at_column(1)
+        },
+        _ => {
+            let column = line.chars()
+                .enumerate()
+                .position(|(i, c)| !(c.is_ascii_digit() || (i == 0 && (c == '-' || c == '+'))))
+                .unwrap_or_else(|| line.chars().count());
+            ParseError::new("Invalid expense")
+                .at_column(column + 1)
+                .expected("integer")
+                .found_char(line.chars().nth(column))
+        }
+    })
+}
+
+/// Expenses sorted in ascending order with duplicates kept, each one along
+/// with the line of the report it came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Multiset {
+    values: Vec<Value>,
+    lines: Vec<usize>
+}
+
+impl Multiset {
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> Copied<std::slice::Iter<'_, Value>> {
+        self.values.iter().copied()
+    }
+
+    /// Values in the order they are in the report.
+    pub fn in_order(&self) -> Vec<Value> {
+        let mut entries: Vec<(usize, Value)> = self.lines.iter().copied().zip(self.iter()).collect();
+        entries.sort_unstable();
+        entries.into_iter().map(|(_, value)| value).collect()
+    }
+
+    /// Lines (1-based) the `found` values came from, in the same order. Every
+    /// value gets a line of its own, so repeated values map to different
+    /// lines. Returns `None` if some value is not there often enough.
+    pub fn lines_of(&self, found: &[Value]) -> Option<Vec<usize>> {
+        let mut used = vec![false; self.values.len()];
+        found.iter()
+            .map(|value| {
+                let from = self.values.partition_point(|v| v < value);
+                let idx = (from..self.values.len())
+                    .take_while(|&idx| self.values[idx] == *value)
+                    .find(|&idx| !used[idx])?;
+                used[idx] = true;
+                Some(self.lines[idx])
+            })
+            .collect()
+    }
+
+}
+
+impl std::iter::FromIterator<(usize, Value)> for Multiset {
+    fn from_iter<T: IntoIterator<Item = (usize, Value)>>(iter: T) -> Self {
+        let mut entries: Vec<(Value, usize)> = iter.into_iter().map(|(line, value)| (value, line)).collect();
+        entries.sort_unstable();
+        let (values, lines) = entries.into_iter().unzip();
+        Multiset { values, lines }
+    }
+}
+
+impl std::iter::FromIterator<Value> for Multiset {
+    fn from_iter<T: IntoIterator<Item = Value>>(iter: T) -> Self {
+        iter.into_iter().enumerate().map(|(idx, value)| (idx + 1, value)).collect()
+    }
+}
+
+pub fn items(input: &str) -> Result<Multiset, ParseError> {
+    let items: Multiset = input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            parse_expense(line)
+                .map(|value| (idx + 1, value))
+                .map_err(|e| e.at_line(idx + 1).with_snippet(line))
+        })
+        .collect::<Result<_, _>>()?;
+    EXPENSES.set(items.len());
+    Ok(items)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Expenses {
+    items: Multiset
+}
+
+impl Expenses {
+
+    pub fn new(items: Multiset) -> Self {
+        Expenses { items }
+    }
+
+    pub fn items(&self) -> &Multiset {
+        &self.items
+    }
+
+    pub fn find_n_sum(&self, n: u8, target: Value) -> Option<Vec<Value>> {
+        self.items.iter().find_n_sum(n, target)
+    }
+
+    pub fn par_find_n_sum(&self, n: u8, target: Value, threads: usize) -> Option<Vec<Value>> {
+        parallel::find_n_sum(self.items.values(), n, target, threads)
+    }
+
+    pub fn find_n_sum_by(&self, strategy: Strategy, n: u8, target: Value) -> Option<Vec<Value>> {
+        self.items.iter().find_n_sum_by(strategy, n, target)
+    }
+
+    pub fn nearest_n_sum(&self, n: u8, target: Value, at_most: bool) -> Option<Nearest<Value>> {
+        self.items.iter().nearest_n_sum(n, target, at_most)
+    }
+
+    /// Run of consecutive expenses summing up to `target`, see [`find_run`].
+    /// Indices of the run are positions of the expenses in the report.
+    pub fn find_run(&self, target: Value) -> Option<Run<Value>> {
+        find_run(&self.items.in_order(), target)
+    }
+
+    /// Subset sums up to `bound`, see [`SubsetSums::new`].
+    pub fn subset_sums(&self, bound: Value, size: Option<u8>) -> Result<SubsetSums, Error> {
+        SubsetSums::new(self.items.values(), bound, size)
+    }
+
+    pub fn n_sums(&self, n: u8, target: Value) -> NSums<Copied<std::slice::Iter<'_, Value>>, Value> {
+        self.items.iter().n_sums(n, target)
+    }
+
+    pub fn count_n_sum(&self, n: u8, target: Value) -> usize {
+        self.items.iter().count_n_sum(n, target)
+    }
+
+    pub fn range_sums(&self, n: u8, range: RangeInclusive<Value>) -> RangeSums<Copied<std::slice::Iter<'_, Value>>, Value> {
+        self.items.iter().range_sums(n, range)
+    }
+
+    pub fn count_range_sums(&self, n: u8, range: RangeInclusive<Value>) -> usize {
+        self.items.iter().count_range_sums(n, range)
+    }
+
+    /// Solves for `n` expenses, reporting the expenses found and the lines
+    /// they are on.
+    pub fn outcome(&self, n: u8, target: Value) -> Outcome {
+        let found = self.find_n_sum(n, target);
+        let product = found.as_deref().map(product::<Answer>);
+        let lines = found.as_ref().and_then(|found| self.items.lines_of(found));
+        let outcome = match product {
+            Some(None) => Outcome::overflowed(),
+            product => Outcome::new(product.flatten())
+        };
+        outcome
+            .with("n", n)
+            .with("target", target)
+            .with("found", found)
+            .with("lines", lines)
+    }
+
+}
+
+impl Solution for Expenses {
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Expenses { items: items(input)? })
+    }
+
+    fn part1(&self) -> Outcome {
+        self.outcome(2, TARGET)
+    }
+
+    fn part2(&self) -> Outcome {
+        self.outcome(3, TARGET)
+    }
+
+}
@@ -0,0 +1,101 @@
+//! Combinations summing up as close to the target as there are. Walks the
+//! same two-pointer scan [`SetIterator::find_n_sum`] does, only keeping
+//! track of the closest pair seen along the way instead of stopping at hits.
+
+use std::cmp::Ordering;
+
+use super::{HEADS, ITERATIONS, Integer, SetIterator};
+use super::combinations::Pair;
+
+/// Combination found by [`SetIterator::nearest_n_sum`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nearest<I> {
+    /// Values found, in the same shape [`SetIterator::find_n_sum`] returns
+    /// them in.
+    pub found: Vec<I>,
+    /// How far the sum of the values is from the target.
+    pub residual: I,
+    /// How the sum of the values compares to the target.
+    pub ordering: Ordering
+}
+
+impl<I: Integer> Nearest<I> {
+
+    fn closer_than(&self, best: &Option<Nearest<I>>) -> bool {
+        best.as_ref().is_none_or(|best| self.residual < best.residual)
+    }
+
+}
+
+/// Distance between `sum` and `target`, `None` if it does not fit.
+fn residual<I: Integer>(sum: Option<I>, target: I, ordering: Ordering) -> Option<I> {
+    match ordering {
+        Ordering::Greater => sum?.checked_sub(target),
+        _ => target.checked_sub(sum?)
+    }
+}
+
+pub(super) fn nearest_n_sum<It, I>(mut iter: It, n: u8, target: I, at_most: bool) -> Option<Nearest<I>> where
+    It: DoubleEndedIterator<Item = I> + Clone,
+    I: Integer
+{
+    let mut best = None;
+    match n {
+        0 => {},
+        1 => {
+            for v in iter {
+                ITERATIONS.inc();
+                let ordering = v.cmp(&target);
+                if at_most && ordering == Ordering::Greater {
+                    break;
+                }
+                if let Some(residual) = residual(Some(v), target, ordering) {
+                    let candidate = Nearest { found: vec![v], residual, ordering };
+                    if candidate.closer_than(&best) {
+                        best = Some(candidate);
+                    }
+                }
+                if ordering != Ordering::Less {
+                    break;
+                }
+            }
+        },
+        2 => {
+            let mut pair = Pair::new(iter, target);
+            while let Some((h, t, ordering)) = pair.step() {
+                if at_most && ordering == Ordering::Greater {
+                    continue;
+                }
+                if let Some(residual) = residual(h.checked_add(t), target, ordering) {
+                    let candidate = Nearest { found: vec![h, t], residual, ordering };
+                    if candidate.closer_than(&best) {
+                        best = Some(candidate);
+                    }
+                }
+                if ordering == Ordering::Equal {
+                    break;
+                }
+            }
+        },
+        _ => {
+            while let Some(h) = iter.next() {
+                HEADS.inc();
+                let rest = match target.checked_sub(h) {
+                    Some(rest) => rest,
+                    None => continue
+                };
+                if let Some(mut candidate) = iter.clone().nearest_n_sum(n - 1, rest, at_most) {
+                    candidate.found.push(h);
+                    let hit = candidate.ordering == Ordering::Equal;
+                    if candidate.closer_than(&best) {
+                        best = Some(candidate);
+                    }
+                    if hit {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    best
+}
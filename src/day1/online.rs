@@ -0,0 +1,95 @@
+//! Detecting pairs and triples summing up to the target as values come in,
+//! one at a time, without having all of them up front.
+//!
+//! Costs of inserting a value, `d` being the number of distinct values so
+//! far:
+//!
+//! * pairs take a single hash map lookup of the complement, `O(1)` expected;
+//! * triples take a lookup of the complement among sums of pairs seen so far,
+//!   plus `O(t)` for the `t` triples it completes;
+//! * keeping those pair sums up to date takes `O(d)` for a value not seen
+//!   before, `O(1)` for a repeated one.
+//!
+//! So `n` insertions cost `O(n * d)` overall, amortized `O(d)` each, and pair
+//! sums take `O(d^2)` memory.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{ITERATIONS, Integer, LOOKUPS};
+
+/// Online detector of pairs and triples summing up to the target.
+#[derive(Debug, Clone)]
+pub struct Detector<I> {
+    target: I,
+    len: usize,
+    /// How many times each value was inserted.
+    counts: HashMap<I, usize>,
+    /// Distinct pairs of values inserted, smaller one first, by their sum.
+    pairs: HashMap<I, Vec<(I, I)>>,
+    /// Combinations reported so far, in ascending order.
+    reported: HashSet<Vec<I>>
+}
+
+impl<I: Integer> Detector<I> {
+
+    pub fn new(target: I) -> Self {
+        Detector { target, len: 0, counts: HashMap::new(), pairs: HashMap::new(), reported: HashSet::new() }
+    }
+
+    pub fn target(&self) -> I {
+        self.target
+    }
+
+    /// Number of values inserted so far, duplicates included.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts `v`, returning pairs and triples summing up to the target it
+    /// completes, each in ascending order. Combinations reported once, even
+    /// if completed again by a repeated value, are not reported again.
+    pub fn insert(&mut self, v: I) -> Vec<Vec<I>> {
+        let mut found = Vec::new();
+        if let Some(rest) = self.target.checked_sub(v) {
+            LOOKUPS.inc();
+            if self.counts.contains_key(&rest) {
+                found.push(sorted(vec![rest, v]));
+            }
+            LOOKUPS.inc();
+            for &(a, b) in self.pairs.get(&rest).into_iter().flatten() {
+                found.push(sorted(vec![a, b, v]));
+            }
+        }
+        found.retain(|combination| self.reported.insert(combination.clone()));
+        self.len += 1;
+        let count = self.counts.entry(v).or_insert(0);
+        *count += 1;
+        match *count {
+            1 => {
+                for &u in self.counts.keys().filter(|&&u| u != v) {
+                    ITERATIONS.inc();
+                    if let Some(sum) = u.checked_add(v) {
+                        self.pairs.entry(sum).or_default().push((u.min(v), u.max(v)));
+                    }
+                }
+            },
+            2 => {
+                if let Some(sum) = v.checked_add(v) {
+                    self.pairs.entry(sum).or_default().push((v, v));
+                }
+            },
+            _ => {}
+        }
+        found
+    }
+
+}
+
+fn sorted<I: Integer>(mut combination: Vec<I>) -> Vec<I> {
+    combination.sort_unstable();
+    combination
+}
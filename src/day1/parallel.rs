@@ -0,0 +1,65 @@
+//! Searching with several threads at once, each one taking its share of the
+//! outermost heads and running the usual [`SetIterator::find_n_sum`] under
+//! them. Heads are dealt out round-robin, so that every thread gets some of
+//! the small ones, which is where solutions are found first.
+//!
+//! Workers keep the lowest index of a head they found a solution under in a
+//! shared atomic, and stop once their next head is past it. That makes the
+//! outcome the same as the sequential search would have. Searches under heads
+//! in flight check it too, before each head of their own, so that they don't
+//! go on for long after it's clear they can't do better. Every worker counts
+//! its own work and adds it to the metrics once done, so counts add up to the
+//! work done by all of them together.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{Integer, Scan, SetIterator, WORKERS, search};
+
+pub(super) fn find_n_sum<I>(values: &[I], n: u8, target: I, threads: usize) -> Option<Vec<I>> where
+    I: Integer + Send + Sync
+{
+    if n < 3 || threads < 2 {
+        return values.iter().copied().find_n_sum(n, target);
+    }
+    let threads = threads.min(values.len()).max(1);
+    WORKERS.set(threads);
+    let best = AtomicUsize::new(usize::MAX);
+    let found = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                let best = &best;
+                scope.spawn(move || {
+                    let mut scan = Scan::new(());
+                    let mut found = None;
+                    for idx in (worker..values.len()).step_by(threads) {
+                        if idx > best.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        scan.heads += 1;
+                        let h = values[idx];
+                        let rest = match target.checked_sub(h) {
+                            Some(rest) => rest,
+                            None => continue
+                        };
+                        scan.cutoff = Some((best, idx));
+                        if let Some(mut values) = search(values[idx + 1..].iter().copied(), n - 1, rest, 1, &mut scan) {
+                            values.push(h);
+                            best.fetch_min(idx, Ordering::Relaxed);
+                            found = Some((idx, values));
+                            break;
+                        }
+                    }
+                    scan.flush();
+                    found
+                })
+            })
+            .collect();
+        workers.into_iter()
+            .map(|worker| worker.join().expect("search worker panicked"))
+            .collect::<Vec<_>>()
+    });
+    found.into_iter()
+        .flatten()
+        .min_by_key(|(idx, _)| *idx)
+        .map(|(_, found)| found)
+}
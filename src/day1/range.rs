@@ -0,0 +1,174 @@
+//! Combinations summing up to anything within a range. Heads are fixed one
+//! by one over the sorted values, as in [`SetIterator::find_n_sum`], with
+//! what's left of the range narrowed down by each of them, until a single
+//! value remains to be picked and that's a scan up to the end of the range.
+
+use std::ops::RangeInclusive;
+
+use super::{HEADS, ITERATIONS, Integer};
+
+/// Inclusive range of sums still reachable, missing ends being unbounded
+/// because the actual bound does not fit into the type.
+#[derive(Debug, Clone, Copy)]
+struct Window<I> {
+    lo: Option<I>,
+    hi: Option<I>
+}
+
+impl<I: Integer> Window<I> {
+
+    fn new(range: &RangeInclusive<I>) -> Self {
+        Window { lo: Some(*range.start()), hi: Some(*range.end()) }
+    }
+
+    fn contains(&self, v: I) -> bool {
+        self.lo.is_none_or(|lo| lo <= v) && !self.below(v)
+    }
+
+    /// Whether `v` and anything greater is past the upper end.
+    fn below(&self, v: I) -> bool {
+        self.hi.is_some_and(|hi| hi < v)
+    }
+
+    /// Whether `count` values no less than `h` sum up past the upper end.
+    fn below_all(&self, h: I, count: u8) -> bool {
+        match (1..count).try_fold(h, |sum, _| sum.checked_add(h)) {
+            Some(sum) => self.below(sum),
+            None => h > I::default()
+        }
+    }
+
+    /// What's left of the window once `h` is taken, `None` if nothing is.
+    fn without(&self, h: I) -> Option<Self> {
+        let positive = h > I::default();
+        let lo = match self.lo.map(|lo| lo.checked_sub(h)) {
+            Some(None) if !positive => return None,
+            Some(lo) => lo,
+            None => None
+        };
+        let hi = match self.hi.map(|hi| hi.checked_sub(h)) {
+            Some(None) if positive => return None,
+            Some(hi) => hi,
+            None => None
+        };
+        Some(Window { lo, hi })
+    }
+
+}
+
+/// Value fixed at some level of the recursion, along with the values left
+/// to pick the rest of the combination from.
+#[derive(Debug, Clone)]
+struct Level<It, I> {
+    iter: It,
+    window: Window<I>,
+    head: Option<I>
+}
+
+/// Iterator over distinct combinations of `n` values summing up to anything
+/// within a range, created with [`super::SetIterator::range_sums`].
+/// Combinations come in ascending order of values.
+#[derive(Debug, Clone)]
+pub struct RangeSums<It, I> {
+    n: u8,
+    levels: Vec<Level<It, I>>
+}
+
+impl<It, I> RangeSums<It, I> where
+    It: DoubleEndedIterator<Item = I> + Clone,
+    I: Integer
+{
+
+    pub(super) fn new(iter: It, n: u8, range: RangeInclusive<I>) -> Self {
+        let levels = match n {
+            0 => Vec::new(),
+            _ => vec![Level { iter, window: Window::new(&range), head: None }]
+        };
+        RangeSums { n, levels }
+    }
+
+}
+
+impl<It, I> Iterator for RangeSums<It, I> where
+    It: DoubleEndedIterator<Item = I> + Clone,
+    I: Integer
+{
+    type Item = Vec<I>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let count = self.n - self.levels.len() as u8 + 1;
+            let level = self.levels.last_mut()?;
+            let last = level.head;
+            let h = match level.iter.by_ref().find(|&v| Some(v) != last) {
+                Some(h) => h,
+                None => {
+                    self.levels.pop();
+                    continue;
+                }
+            };
+            level.head = Some(h);
+            if count == 1 {
+                ITERATIONS.inc();
+                if level.window.below(h) {
+                    self.levels.pop();
+                    continue;
+                }
+                if level.window.contains(h) {
+                    return Some(self.levels.iter().filter_map(|level| level.head).collect());
+                }
+                continue;
+            }
+            HEADS.inc();
+            if level.window.below_all(h, count) {
+                self.levels.pop();
+                continue;
+            }
+            if let Some(window) = level.window.without(h) {
+                let iter = level.iter.clone();
+                self.levels.push(Level { iter, window, head: None });
+            }
+        }
+    }
+}
+
+/// Counts what [`RangeSums`] would yield, without materializing anything.
+pub(super) fn count_range_sums<It, I>(iter: It, n: u8, range: RangeInclusive<I>) -> usize where
+    It: DoubleEndedIterator<Item = I> + Clone,
+    I: Integer
+{
+    match n {
+        0 => 0,
+        _ => count_within(iter, n, Window::new(&range))
+    }
+}
+
+fn count_within<It, I>(mut iter: It, count: u8, window: Window<I>) -> usize where
+    It: DoubleEndedIterator<Item = I> + Clone,
+    I: Integer
+{
+    let mut total = 0;
+    let mut last = None;
+    while let Some(h) = iter.next() {
+        if Some(h) == last {
+            continue;
+        }
+        last = Some(h);
+        if count == 1 {
+            ITERATIONS.inc();
+            if window.below(h) {
+                break;
+            }
+            total += window.contains(h) as usize;
+            continue;
+        }
+        HEADS.inc();
+        if window.below_all(h, count) {
+            break;
+        }
+        if let Some(window) = window.without(h) {
+            total += count_within(iter.clone(), count - 1, window);
+        }
+    }
+    total
+}
@@ -0,0 +1,189 @@
+//! Alternative engines for finding `n` values summing up to the target, all
+//! of them reachable through [`SetIterator::find_n_sum_by`].
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use crate::Error;
+use super::{HEADS, ITERATIONS, Integer, LOOKUPS, PARTIALS, SetIterator};
+
+/// How [`SetIterator::find_n_sum_by`] searches for a combination.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Strategy {
+    /// Two-pointer scan at the bottom, heads fixed one by one above it over
+    /// clones of the iterator, as [`SetIterator::find_n_sum`] does.
+    #[default]
+    TwoPointer,
+    /// Lookups of the complement in a hash set at the bottom, heads fixed one
+    /// by one above it. Counts hash set probes as lookups.
+    HashSet,
+    /// Two-pointer scan over indices into the sorted values, heads fixed one
+    /// by one above it by narrowing down the slice instead of cloning.
+    Indexed,
+    /// Sums of every combination of `n / 2` values put into a hash map, then
+    /// looked up from every combination of the remaining size. Counts those
+    /// combinations as partials and hash map probes as lookups.
+    MeetInTheMiddle
+}
+
+impl Strategy {
+
+    pub const ALL: [Strategy; 4] = [
+        Strategy::TwoPointer,
+        Strategy::HashSet,
+        Strategy::Indexed,
+        Strategy::MeetInTheMiddle
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::TwoPointer => "two-pointer",
+            Strategy::HashSet => "hash-set",
+            Strategy::Indexed => "indexed",
+            Strategy::MeetInTheMiddle => "meet-in-the-middle"
+        }
+    }
+
+}
+
+impl std::fmt::Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Strategy {
+
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Strategy::ALL.iter()
+            .copied()
+            .find(|strategy| strategy.name() == s)
+            .ok_or_else(|| Error::usage(format!("Invalid strategy: {}", s)))
+    }
+
+}
+
+pub(super) fn find_n_sum<It, I>(strategy: Strategy, iter: It, n: u8, target: I) -> Option<Vec<I>> where
+    It: DoubleEndedIterator<Item = I> + Clone,
+    I: Integer
+{
+    if n < 2 {
+        return iter.find_n_sum(n, target);
+    }
+    match strategy {
+        Strategy::TwoPointer => iter.find_n_sum(n, target),
+        Strategy::HashSet => hashed(&iter.collect::<Vec<_>>(), n, target),
+        Strategy::Indexed => {
+            let mut values: Vec<_> = iter.collect();
+            values.sort_unstable();
+            indexed(&values, n, target)
+        },
+        Strategy::MeetInTheMiddle => meet(&iter.collect::<Vec<_>>(), n, target)
+    }
+}
+
+fn hashed<I: Integer>(values: &[I], n: u8, target: I) -> Option<Vec<I>> {
+    if n == 2 {
+        let mut seen = HashSet::with_capacity(values.len());
+        for &v in values {
+            LOOKUPS.inc();
+            match target.checked_sub(v) {
+                Some(rest) if seen.contains(&rest) => return Some(vec![rest, v]),
+                _ => {}
+            }
+            seen.insert(v);
+        }
+        return None;
+    }
+    for (idx, &h) in values.iter().enumerate() {
+        HEADS.inc();
+        let rest = match target.checked_sub(h) {
+            Some(rest) => rest,
+            None => continue
+        };
+        if let Some(mut found) = hashed(&values[idx + 1..], n - 1, rest) {
+            found.push(h);
+            return Some(found);
+        }
+    }
+    None
+}
+
+fn indexed<I: Integer>(values: &[I], n: u8, target: I) -> Option<Vec<I>> {
+    if n == 2 {
+        let (mut lo, mut hi) = (0, values.len().checked_sub(1)?);
+        while lo < hi {
+            ITERATIONS.inc();
+            match values[lo].cmp_sum(values[hi], target) {
+                Ordering::Equal => return Some(vec![values[lo], values[hi]]),
+                Ordering::Less => lo += 1,
+                Ordering::Greater => hi -= 1
+            }
+        }
+        return None;
+    }
+    for (idx, &h) in values.iter().enumerate() {
+        HEADS.inc();
+        let rest = match target.checked_sub(h) {
+            Some(rest) => rest,
+            None => continue
+        };
+        if let Some(mut found) = indexed(&values[idx + 1..], n - 1, rest) {
+            found.push(h);
+            return Some(found);
+        }
+    }
+    None
+}
+
+fn meet<I: Integer>(values: &[I], n: u8, target: I) -> Option<Vec<I>> {
+    let half = n as usize / 2;
+    let mut sums: HashMap<I, Vec<Vec<usize>>> = HashMap::new();
+    each_combination(values.len(), half, |idx| {
+        PARTIALS.inc();
+        if let Some(sum) = sum_of(values, idx) {
+            sums.entry(sum).or_default().push(idx.to_vec());
+        }
+        false
+    });
+    let mut found = None;
+    each_combination(values.len(), n as usize - half, |idx| {
+        PARTIALS.inc();
+        LOOKUPS.inc();
+        let other = sum_of(values, idx)
+            .and_then(|sum| target.checked_sub(sum))
+            .and_then(|rest| sums.get(&rest))
+            .and_then(|others| others.iter().find(|other| other.iter().all(|i| !idx.contains(i))));
+        if let Some(other) = other {
+            found = Some(other.iter().chain(idx).map(|&i| values[i]).collect());
+        }
+        found.is_some()
+    });
+    found
+}
+
+/// Sum of the values at `idx`, `None` if it does not fit into the type.
+fn sum_of<I: Integer>(values: &[I], idx: &[usize]) -> Option<I> {
+    idx[1..].iter().try_fold(values[idx[0]], |sum, &i| sum.checked_add(values[i]))
+}
+
+/// Calls `f` with indices of every `size` out of `len` elements, in
+/// lexicographic order, until it returns `true`.
+fn each_combination(len: usize, size: usize, mut f: impl FnMut(&[usize]) -> bool) {
+    if size == 0 || size > len {
+        return;
+    }
+    let mut idx: Vec<usize> = (0..size).collect();
+    while !f(&idx) {
+        let pos = match (0..size).rev().find(|&pos| idx[pos] < len - size + pos) {
+            Some(pos) => pos,
+            None => return
+        };
+        idx[pos] += 1;
+        for next in pos + 1..size {
+            idx[next] = idx[next - 1] + 1;
+        }
+    }
+}
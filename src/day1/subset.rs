@@ -0,0 +1,161 @@
+//! Subset sums by dynamic programming over bitsets: bit `s` of a row tells
+//! whether some subset of the values seen so far sums up to `s`, and taking
+//! a value `v` into account is ORing the row with itself shifted by `v`.
+//!
+//! Every row is kept around so that a witness subset can be traced back from
+//! the last one. That's `O(n * bound / 64)` words of memory, times `k + 1`
+//! when the subset size is restricted to `k`, since then there's a row per
+//! each size up to `k`. Tables taking more than a gigabyte are refused.
+
+use std::ops::RangeInclusive;
+
+use bitset_core::BitSet;
+
+use crate::{Error, ParseError};
+use super::{SHIFTS, Value};
+
+const BITS_PER_WORD: usize = 64;
+
+/// Largest table of rows [`SubsetSums::new`] is willing to allocate.
+const MAX_BYTES: usize = 1 << 30;
+
+/// Sums reachable by subsets of the values, up to a bound.
+#[derive(Debug, Clone)]
+pub struct SubsetSums {
+    values: Vec<Value>,
+    bound: usize,
+    size: Option<u8>,
+    words: usize,
+    /// Rows after taking each prefix of `values` into account, empty prefix
+    /// first. With the subset size restricted, every row is made of layers
+    /// for each size, back to back.
+    rows: Vec<Vec<u64>>
+}
+
+/// `dst |= src << shift`, bits shifted past the end are dropped.
+fn shift_or(dst: &mut [u64], src: &[u64], shift: usize) {
+    let (words, bits) = (shift / BITS_PER_WORD, shift % BITS_PER_WORD);
+    for (from, word) in dst.iter_mut().skip(words).enumerate() {
+        *word |= src[from] << bits;
+        if bits > 0 && from > 0 {
+            *word |= src[from - 1] >> (BITS_PER_WORD - bits);
+        }
+    }
+    SHIFTS.add(dst.len() - words.min(dst.len()));
+}
+
+impl SubsetSums {
+
+    /// Finds every sum up to `bound` subsets of `values` add up to, subsets
+    /// of exactly `size` values if that's given. Fails if some of the values
+    /// are negative, there's no bounding sums then, or if the table would
+    /// take more than a gigabyte.
+    pub fn new(values: &[Value], bound: Value, size: Option<u8>) -> Result<Self, Error> {
+        if bound < 0 {
+            return Err(Error::usage(format!("Bound must not be negative: {}", bound)));
+        }
+        if let Some(&v) = values.iter().find(|&&v| v < 0) {
+            return Err(Error::Parse(ParseError::new("Negative expense")
+                .expected("non-negative expenses for subset sums")
+                .found(v.to_string())));
+        }
+        let layers = size.map_or(1, |k| k as usize + 1);
+        let words = bound as usize / BITS_PER_WORD + 1;
+        let bytes = words.checked_mul(layers)
+            .and_then(|words| words.checked_mul(values.len() + 1))
+            .and_then(|words| words.checked_mul(BITS_PER_WORD / 8));
+        if bytes.is_none_or(|bytes| bytes > MAX_BYTES) {
+            return Err(Error::usage(format!(
+                "Subset sums up to {} over {} expenses take more than {} MiB", bound, values.len(), MAX_BYTES >> 20
+            )));
+        }
+        let bound = bound as usize;
+        let mut first = vec![0; words * layers];
+        first.bit_set(0);
+        let mut rows = Vec::with_capacity(values.len() + 1);
+        rows.push(first);
+        for &v in values {
+            let prev = rows.last().expect("at least the empty prefix");
+            let mut row = prev.clone();
+            if v as usize <= bound {
+                match size {
+                    None => shift_or(&mut row, prev, v as usize),
+                    Some(_) => {
+                        for layer in 1..layers {
+                            let src = &prev[(layer - 1) * words..layer * words];
+                            shift_or(&mut row[layer * words..(layer + 1) * words], src, v as usize);
+                        }
+                    }
+                }
+            }
+            rows.push(row);
+        }
+        Ok(SubsetSums { values: values.to_vec(), bound, size, words, rows })
+    }
+
+    pub fn bound(&self) -> Value {
+        self.bound as Value
+    }
+
+    pub fn size(&self) -> Option<u8> {
+        self.size
+    }
+
+    /// Bits of the row for subsets of the right size, out of `row`.
+    fn layer<'a>(&self, row: &'a [u64]) -> &'a [u64] {
+        let layer = self.size.map_or(0, |k| k as usize);
+        &row[layer * self.words..(layer + 1) * self.words]
+    }
+
+    fn last(&self) -> &[u64] {
+        self.layer(self.rows.last().expect("at least the empty prefix"))
+    }
+
+    /// Tells whether some subset sums up to `target`. With the size not
+    /// restricted that includes the empty subset, so 0 is always reachable.
+    pub fn reachable(&self, target: Value) -> bool {
+        target >= 0 && target as usize <= self.bound && self.last().bit_test(target as usize)
+    }
+
+    /// Number of reachable sums up to the bound.
+    pub fn count(&self) -> usize {
+        (0..=self.bound).filter(|&s| self.last().bit_test(s)).count()
+    }
+
+    /// Reachable sums up to the bound, coalesced into ranges.
+    pub fn ranges(&self) -> Vec<RangeInclusive<Value>> {
+        let last = self.last();
+        let mut ranges: Vec<RangeInclusive<Value>> = Vec::new();
+        for s in (0..=self.bound).filter(|&s| last.bit_test(s)) {
+            let s = s as Value;
+            match ranges.last_mut() {
+                Some(range) if *range.end() + 1 == s => *range = *range.start()..=s,
+                _ => ranges.push(s..=s)
+            }
+        }
+        ranges
+    }
+
+    /// Some subset summing up to `target`, in ascending order.
+    pub fn witness(&self, target: Value) -> Option<Vec<Value>> {
+        if !self.reachable(target) {
+            return None;
+        }
+        let mut sum = target as usize;
+        let mut layer = self.size.map_or(0, |k| k as usize);
+        let mut found = Vec::new();
+        for (idx, &v) in self.values.iter().enumerate().rev() {
+            let prev = &self.rows[idx][layer * self.words..(layer + 1) * self.words];
+            if !prev.bit_test(sum) {
+                found.push(v);
+                sum -= v as usize;
+                if self.size.is_some() {
+                    layer -= 1;
+                }
+            }
+        }
+        found.reverse();
+        Some(found)
+    }
+
+}
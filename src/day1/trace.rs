@@ -0,0 +1,176 @@
+//! Tracing what [`SetIterator::find_n_sum`] does on its way to the answer.
+//!
+//! The search reports every step it takes to a [`Tracer`]: each head it fixes
+//! along with the partial target left for the rest, each move of the two
+//! pointers at the bottom, and how each level of the recursion ends up.
+//! [`Trace`] collects those events, to be printed as an indented trace or
+//! exported as an event log. Searching with the `()` tracer costs nothing.
+//!
+//! [`SetIterator::find_n_sum`]: super::SetIterator::find_n_sum
+
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+
+use crate::json::Json;
+
+/// Something happening during the search, `depth` levels deep into the
+/// recursion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<I> {
+    /// Looking for `n` values summing up to `target`.
+    Search { depth: usize, n: u8, target: I },
+    /// Fixed `head`, leaving `rest` for the other values, if it fits.
+    Head { depth: usize, head: I, rest: Option<I> },
+    /// Compared `head + tail` to `target`. Head moves on when the sum is less,
+    /// tail moves on when it's greater.
+    Step { depth: usize, head: I, tail: I, target: I, sum: Ordering },
+    /// Done with the level, with or without values found.
+    Done { depth: usize, found: Option<Vec<I>> }
+}
+
+impl<I> Event<I> {
+
+    pub fn depth(&self) -> usize {
+        match *self {
+            Event::Search { depth, .. } |
+            Event::Head { depth, .. } |
+            Event::Step { depth, .. } |
+            Event::Done { depth, .. } => depth
+        }
+    }
+
+}
+
+impl<I: Display> Display for Event<I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Search { n, target, .. } =>
+                write!(f, "find {} summing up to {}", n, target),
+            Event::Head { head, rest: Some(rest), .. } =>
+                write!(f, "head {}, looking for {}", head, rest),
+            Event::Head { head, rest: None, .. } =>
+                write!(f, "head {}, rest overflows: skipped", head),
+            Event::Step { head, tail, target, sum, .. } => match sum {
+                Ordering::Less => write!(f, "{} + {} < {}: head moves", head, tail, target),
+                Ordering::Greater => write!(f, "{} + {} > {}: tail moves", head, tail, target),
+                Ordering::Equal => write!(f, "{} + {} = {}", head, tail, target)
+            },
+            Event::Done { found: Some(found), .. } => {
+                let found: Vec<String> = found.iter().map(ToString::to_string).collect();
+                write!(f, "found {}", found.join(", "))
+            },
+            Event::Done { found: None, .. } =>
+                f.write_str("exhausted")
+        }
+    }
+}
+
+impl<I: Into<Json>> From<Event<I>> for Json {
+    fn from(event: Event<I>) -> Self {
+        let depth = event.depth();
+        let json = |kind: &str| Json::object().with("event", kind).with("depth", depth);
+        match event {
+            Event::Search { n, target, .. } =>
+                json("search").with("n", n).with("target", target),
+            Event::Head { head, rest, .. } =>
+                json("head").with("head", head).with("rest", rest),
+            Event::Step { head, tail, target, sum, .. } => {
+                let sum = match sum {
+                    Ordering::Less => "less",
+                    Ordering::Equal => "equal",
+                    Ordering::Greater => "greater"
+                };
+                json("step").with("head", head).with("tail", tail).with("target", target).with("sum", sum)
+            },
+            Event::Done { found, .. } =>
+                json("done").with("found", found)
+        }
+    }
+}
+
+/// Receiver of search events.
+pub trait Tracer<I> {
+
+    /// Whether the search should bother reporting events at all.
+    const ENABLED: bool = true;
+
+    fn event(&mut self, event: Event<I>);
+
+}
+
+/// No tracing whatsoever.
+impl<I> Tracer<I> for () {
+
+    const ENABLED: bool = false;
+
+    fn event(&mut self, _: Event<I>) {}
+
+}
+
+/// Tracing into a tracer the caller keeps hold of.
+impl<I, T: Tracer<I> + ?Sized> Tracer<I> for &mut T {
+
+    const ENABLED: bool = T::ENABLED;
+
+    fn event(&mut self, event: Event<I>) {
+        (**self).event(event)
+    }
+
+}
+
+/// Every event of a search, in the order they happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace<I> {
+    events: Vec<Event<I>>
+}
+
+impl<I> Trace<I> {
+
+    pub fn new() -> Self {
+        Trace { events: Vec::new() }
+    }
+
+    pub fn events(&self) -> &[Event<I>] {
+        &self.events
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+}
+
+impl<I: Clone + Into<Json>> Trace<I> {
+
+    /// Event log, an array of objects tagged with their `event` kind.
+    pub fn to_json(&self) -> Json {
+        Json::Array(self.events.iter().cloned().map(Into::into).collect())
+    }
+
+}
+
+impl<I> Default for Trace<I> {
+    fn default() -> Self {
+        Trace::new()
+    }
+}
+
+impl<I> Tracer<I> for Trace<I> {
+    fn event(&mut self, event: Event<I>) {
+        self.events.push(event);
+    }
+}
+
+/// Indented trace, an event per line, two spaces per level of recursion.
+impl<I: Display> Display for Trace<I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for event in &self.events {
+            writeln!(f, "{:indent$}{}", "", event, indent = 2 * event.depth())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+//! Runs of consecutive values, in the order they come in, summing up to the
+//! target. A sliding window does when no value is negative, since extending
+//! a window never decreases its sum then. Otherwise it takes prefix sums,
+//! with a run being a pair of equal prefix sums once the target is taken off
+//! the later one.
+
+use std::collections::HashMap;
+
+use super::{ITERATIONS, Integer, LOOKUPS};
+
+/// Run of at least two consecutive values, `start` and `end` being indices
+/// of the first and the last one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run<I> {
+    pub start: usize,
+    pub end: usize,
+    pub min: I,
+    pub max: I
+}
+
+impl<I: Integer> Run<I> {
+
+    fn new(values: &[I], start: usize, end: usize) -> Self {
+        let run = &values[start..=end];
+        let min = *run.iter().min().expect("runs are never empty");
+        let max = *run.iter().max().expect("runs are never empty");
+        Run { start, end, min, max }
+    }
+
+    /// Smallest and largest values of the run added up, `None` if that
+    /// overflows.
+    pub fn min_max_sum(&self) -> Option<I> {
+        self.min.checked_add(self.max)
+    }
+
+}
+
+/// Finds the run summing up to `target` which ends first, the longest one
+/// if there are several. Gives up on runs once a sum of consecutive values
+/// does not fit into the type.
+pub fn find_run<I: Integer>(values: &[I], target: I) -> Option<Run<I>> {
+    match values.iter().all(|&v| v >= I::default()) {
+        true => sliding(values, target),
+        false => prefixed(values, target)
+    }
+}
+
+fn sliding<I: Integer>(values: &[I], target: I) -> Option<Run<I>> {
+    let mut start = 0;
+    let mut sum = I::default();
+    for (end, &v) in values.iter().enumerate() {
+        sum = loop {
+            ITERATIONS.inc();
+            match sum.checked_add(v) {
+                Some(sum) => break sum,
+                None => {
+                    sum = sum.checked_sub(values[start])?;
+                    start += 1;
+                }
+            }
+        };
+        while sum > target && start <= end {
+            ITERATIONS.inc();
+            sum = sum.checked_sub(values[start])?;
+            start += 1;
+        }
+        if sum == target && start < end {
+            return Some(Run::new(values, start, end));
+        }
+    }
+    None
+}
+
+fn prefixed<I: Integer>(values: &[I], target: I) -> Option<Run<I>> {
+    let mut prefixes = Vec::with_capacity(values.len() + 1);
+    prefixes.push(I::default());
+    for &v in values {
+        ITERATIONS.inc();
+        prefixes.push(prefixes[prefixes.len() - 1].checked_add(v)?);
+    }
+    // Earliest index each prefix sum shows up at, only those leaving at
+    // least two values to the run.
+    let mut firsts = HashMap::new();
+    for end in 1..values.len() {
+        firsts.entry(prefixes[end - 1]).or_insert(end - 1);
+        LOOKUPS.inc();
+        let start = prefixes[end + 1].checked_sub(target).and_then(|rest| firsts.get(&rest));
+        if let Some(&start) = start {
+            return Some(Run::new(values, start, end));
+        }
+    }
+    None
+}
@@ -0,0 +1,136 @@
+//! Richer policies: character classes instead of single characters, and
+//! several requirements combined on a single line.
+
+use super::{Policy, PolicyRule};
+
+/// Characters a policy is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Class {
+    /// Single character, as in `a`.
+    Char(char),
+    /// Any of the characters or character ranges listed, as in `[a-fx]`.
+    Set(Vec<(char, char)>),
+    /// Named class of ASCII characters, as in `digit`.
+    Named(Named)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Named {
+    Digit,
+    Lower,
+    Upper,
+    Alpha,
+    Alnum,
+    Punct
+}
+
+impl Named {
+
+    pub const ALL: [Named; 6] = [
+        Named::Digit,
+        Named::Lower,
+        Named::Upper,
+        Named::Alpha,
+        Named::Alnum,
+        Named::Punct
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Named::Digit => "digit",
+            Named::Lower => "lower",
+            Named::Upper => "upper",
+            Named::Alpha => "alpha",
+            Named::Alnum => "alnum",
+            Named::Punct => "punct"
+        }
+    }
+
+    fn contains(&self, c: char) -> bool {
+        match self {
+            Named::Digit => c.is_ascii_digit(),
+            Named::Lower => c.is_ascii_lowercase(),
+            Named::Upper => c.is_ascii_uppercase(),
+            Named::Alpha => c.is_ascii_alphabetic(),
+            Named::Alnum => c.is_ascii_alphanumeric(),
+            Named::Punct => c.is_ascii_punctuation()
+        }
+    }
+
+}
+
+impl Class {
+
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            Class::Char(ch) => *ch == c,
+            Class::Set(ranges) => ranges.iter().any(|&(from, to)| (from..=to).contains(&c)),
+            Class::Named(named) => named.contains(c)
+        }
+    }
+
+}
+
+/// Parsed policy line, walked by [`Expr::valid`] to check a password.
+///
+/// Constraints separated by commas all have to hold, each of them on its
+/// own: commas bind looser than anything else, so `a or b, c` means
+/// `(a or b) and c`. Otherwise `not` binds tighter than `and`, which binds
+/// tighter than `or`; parentheses group as usual.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    /// Character class within a range, as in `1-3 a`, checked the way the
+    /// rule says.
+    Policy(Policy),
+    /// Password is at least that many characters long, as in `min-length 8`,
+    /// whatever the rule.
+    MinLength(usize),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>)
+}
+
+impl Expr {
+
+    /// Whether `password` satisfies the expression, with policies checked
+    /// according to `rule`.
+    pub fn valid(&self, rule: &dyn PolicyRule, password: &str) -> bool {
+        match self {
+            Expr::Policy(policy) => rule.valid(policy, password),
+            Expr::MinLength(len) => password.chars().count() >= *len,
+            Expr::Not(expr) => !expr.valid(rule, password),
+            Expr::And(exprs) => exprs.iter().all(|expr| expr.valid(rule, password)),
+            Expr::Or(exprs) => exprs.iter().any(|expr| expr.valid(rule, password))
+        }
+    }
+
+    /// The policy itself if that's all there is to the expression, as is the
+    /// case with the puzzle input.
+    pub fn policy(&self) -> Option<&Policy> {
+        match self {
+            Expr::Policy(policy) => Some(policy),
+            _ => None
+        }
+    }
+
+    pub(super) fn and(self, rhs: Expr) -> Expr {
+        match self {
+            Expr::And(mut exprs) => {
+                exprs.push(rhs);
+                Expr::And(exprs)
+            },
+            lhs => Expr::And(vec![lhs, rhs])
+        }
+    }
+
+    pub(super) fn or(self, rhs: Expr) -> Expr {
+        match self {
+            Expr::Or(mut exprs) => {
+                exprs.push(rhs);
+                Expr::Or(exprs)
+            },
+            lhs => Expr::Or(vec![lhs, rhs])
+        }
+    }
+
+}
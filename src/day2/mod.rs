@@ -0,0 +1,338 @@
+//! Day 2: Password Philosophy, checking passwords against their policies.
+//!
+//! Besides the puzzle's own `1-3 a: abcde` lines, policies may use character
+//! classes and combine several requirements, see [`Expr`]:
+//!
+//! ```text
+//! 1-3 [a-f], 2-9 digit: 1a2b3c4d
+//! min-length 8 and not 1-20 punct: hunter22
+//! (1-1 upper or 2-4 [!?]) and 1-3 b: Abba
+//! ```
+
+use std::ops::RangeInclusive;
+
+use crate::{Answer, Error, Outcome, ParseError, Part, Solution};
+use crate::metrics::Metric;
+
+mod expr;
+
+pub use expr::{Class, Expr, Named};
+
+pub static LINES_PARSED: Metric = Metric::counter("day2.lines_parsed");
+pub static LINES_REJECTED: Metric = Metric::counter("day2.lines_rejected");
+pub static PASSWORDS_CHECKED: Metric = Metric::counter("day2.passwords_checked");
+
+/// Every day 2 metric, for [`crate::metrics`] to reset and report.
+pub static METRICS: &[&Metric] = &[&LINES_PARSED, &LINES_REJECTED, &PASSWORDS_CHECKED];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+    range: RangeInclusive<usize>,
+    class: Class
+}
+
+impl Policy {
+
+    /// Policy demanding characters of the `class` be found within the
+    /// `range`, which is a range of counts as well as a pair of positions.
+    /// Positions count from 1, so does the range, and it must not be inverted.
+    pub fn new(range: RangeInclusive<usize>, class: Class) -> Result<Self, ParseError> {
+        let (from, to) = (*range.start(), *range.end());
+        if from == 0 || to == 0 {
+            return Err(ParseError::new("Invalid policy")
+                .expected("positions counting from 1")
+                .found(format!("{}-{}", from, to)));
+        }
+        if from > to {
+            return Err(ParseError::new("Invalid policy")
+                .expected("range going up")
+                .found(format!("{}-{}", from, to)));
+        }
+        Ok(Policy { range, class })
+    }
+
+    pub fn range(&self) -> &RangeInclusive<usize> {
+        &self.range
+    }
+
+    pub fn class(&self) -> &Class {
+        &self.class
+    }
+
+    /// Password holds characters of the class a number of times within the
+    /// range.
+    pub fn occurrences_valid(&self, password: &str) -> bool {
+        PASSWORDS_CHECKED.inc();
+        let occurences = password.chars().filter(|&c| self.class.contains(c)).count();
+        self.range.contains(&occurences)
+    }
+
+    /// Character of the class occupies exactly one of the two (1-based)
+    /// positions.
+    pub fn positions_valid(&self, password: &str) -> bool {
+        PASSWORDS_CHECKED.inc();
+        let occurences = password.chars()
+            .enumerate()
+            .filter(|&(i, c)| {
+                (i == self.range.start() - 1 || i == self.range.end() - 1) &&
+                    self.class.contains(c)
+            })
+            .count();
+        occurences == 1
+    }
+
+}
+
+/// Interpretation of what a policy demands of a password.
+pub trait PolicyRule {
+
+    fn name(&self) -> &'static str;
+
+    /// Puzzle part the rule answers, if it's one of the puzzle's own.
+    fn part(&self) -> Option<Part> {
+        None
+    }
+
+    fn valid(&self, policy: &Policy, password: &str) -> bool;
+
+}
+
+/// Policy character occurs a number of times within the range, as in the
+/// first part.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Occurrences;
+
+impl PolicyRule for Occurrences {
+
+    fn name(&self) -> &'static str {
+        "occurrences"
+    }
+
+    fn part(&self) -> Option<Part> {
+        Some(Part::One)
+    }
+
+    fn valid(&self, policy: &Policy, password: &str) -> bool {
+        policy.occurrences_valid(password)
+    }
+
+}
+
+/// Policy character occupies exactly one of the two positions, as in the
+/// second part.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Positions;
+
+impl PolicyRule for Positions {
+
+    fn name(&self) -> &'static str {
+        "positions"
+    }
+
+    fn part(&self) -> Option<Part> {
+        Some(Part::Two)
+    }
+
+    fn valid(&self, policy: &Policy, password: &str) -> bool {
+        policy.positions_valid(password)
+    }
+
+}
+
+/// All the known rules, ordered by the puzzle part they answer.
+pub const RULES: &[&dyn PolicyRule] = &[&Occurrences, &Positions];
+
+/// Known rule going by the given name.
+pub fn rule(name: &str) -> Result<&'static dyn PolicyRule, Error> {
+    RULES.iter()
+        .copied()
+        .find(|rule| rule.name() == name)
+        .ok_or_else(|| Error::usage(format!("Invalid rule: {}", name)))
+}
+
+/// Counts of passwords valid under each of several rules, all of them
+/// evaluated in a single pass over the entries.
+pub struct Tally<'a> {
+    rules: Vec<&'a dyn PolicyRule>,
+    valid: Vec<usize>,
+    total: usize
+}
+
+impl<'a> Tally<'a> {
+
+    pub fn new(rules: Vec<&'a dyn PolicyRule>) -> Self {
+        let valid = vec![0; rules.len()];
+        Tally { rules, valid, total: 0 }
+    }
+
+    pub fn check(&mut self, expr: &Expr, password: &str) {
+        self.total += 1;
+        for (rule, valid) in self.rules.iter().zip(&mut self.valid) {
+            if expr.valid(*rule, password) {
+                *valid += 1;
+            }
+        }
+    }
+
+    /// Rules along with how many passwords each of them found valid.
+    pub fn counts(&self) -> impl Iterator<Item = (&'a dyn PolicyRule, usize)> + '_ {
+        self.rules.iter().copied().zip(self.valid.iter().copied())
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+}
+
+/// Policy or combination of them parsed, unless it turned out to be invalid,
+/// in which case the error comes along with the byte offset of the policy.
+type Parsed = Result<Expr, (usize, ParseError)>;
+
+fn both(lhs: Parsed, rhs: Parsed, combine: fn(Expr, Expr) -> Expr) -> Parsed {
+    Ok(combine(lhs?, rhs?))
+}
+
+peg::parser! {
+    pub grammar password_policy_parser() for str {
+        rule __()
+            = [' ' | '\t']+
+        rule _()
+            = [' ' | '\t']*
+        rule char() -> char
+            = c:$([^ ' ' | '\t']) { c.chars().next().unwrap() }
+        rule set_char() -> char
+            = c:$([^ ']' | ' ' | '\t']) { c.chars().next().unwrap() }
+        rule integer() -> usize
+            = n:$(['1'..='9']['0'..='9']+ / ['0'..='9']) {? n.parse().or(Err("usize")) }
+        rule set_item() -> (char, char)
+            = from:set_char() to:("-" to:set_char() { to })? {?
+                match to {
+                    Some(to) if to < from => Err("character range going up"),
+                    Some(to) => Ok((from, to)),
+                    None => Ok((from, from))
+                }
+            }
+        rule named() -> Named
+            = n:$(['a'..='z']+) {?
+                Named::ALL.iter().copied().find(|named| named.name() == n).ok_or("character class")
+            }
+        rule class() -> Class
+            = "[" items:set_item()+ "]" { Class::Set(items) }
+            / n:named() { Class::Named(n) }
+            / c:char() { Class::Char(c) }
+        rule constraint() -> Parsed
+            = p:position!() from:integer() "-" to:integer() __ class:class() {
+                Policy::new(from..=to, class).map(Expr::Policy).map_err(|e| (p, e))
+            }
+            / "min-length" __ n:integer() { Ok(Expr::MinLength(n)) }
+        rule term() -> Parsed
+            = "not" _ e:term() { e.map(|e| Expr::Not(Box::new(e))) }
+            / "(" _ e:list() _ ")" { e }
+            / constraint()
+        rule conjunction() -> Parsed
+            = first:term() rest:(__ "and" __ e:term() { e })* {
+                rest.into_iter().fold(first, |lhs, rhs| both(lhs, rhs, Expr::and))
+            }
+        rule expr() -> Parsed
+            = first:conjunction() rest:(__ "or" __ e:conjunction() { e })* {
+                rest.into_iter().fold(first, |lhs, rhs| both(lhs, rhs, Expr::or))
+            }
+        rule list() -> Parsed
+            = first:expr() rest:(_ "," _ e:expr() { e })* {
+                rest.into_iter().fold(first, |lhs, rhs| both(lhs, rhs, Expr::and))
+            }
+        pub rule root() -> (Parsed, &'input str)
+            = e:list() _ ":" _ pwd:$([^ ' ' | '\t']+) { (e, pwd) }
+    }
+}
+
+/// Parses a single line of input, errors point at line 1. Policies are
+/// validated on the way, see [`Policy::new`].
+pub fn parse_line(line: &str) -> Result<(Expr, &str), ParseError> {
+    let parsed = password_policy_parser::root(line)
+        .map_err(|e| {
+            let found = line[e.location.offset..].chars().next();
+            ParseError::from(e).found_char(found)
+        })
+        .and_then(|(expr, password)| {
+            let expr = expr.map_err(|(offset, e)| e.at_line(1).at_column(line[..offset].chars().count() + 1))?;
+            Ok((expr, password))
+        })
+        .map_err(|e| {
+            LINES_REJECTED.inc();
+            e.with_snippet(line)
+        })?;
+    LINES_PARSED.inc();
+    Ok(parsed)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Passwords {
+    entries: Vec<(Expr, String)>
+}
+
+impl Passwords {
+
+    pub fn entries(&self) -> impl Iterator<Item = (&Expr, &str)> + '_ {
+        self.entries.iter().map(|(expr, password)| (expr, password.as_str()))
+    }
+
+    /// Parses what it can, skipping malformed lines instead of giving up on
+    /// the first one. Diagnostics of the lines skipped come along.
+    pub fn parse_lenient(input: &str) -> (Self, Vec<ParseError>) {
+        let mut entries = Vec::new();
+        let mut skipped = Vec::new();
+        for (idx, line) in input.lines().enumerate() {
+            match parse_line(line) {
+                Ok((expr, password)) => entries.push((expr, password.to_owned())),
+                Err(e) => skipped.push(e.at_line(idx + 1))
+            }
+        }
+        (Passwords { entries }, skipped)
+    }
+
+    /// Checks every password against all the `rules` in a single pass.
+    pub fn tally<'a>(&self, rules: Vec<&'a dyn PolicyRule>) -> Tally<'a> {
+        let mut tally = Tally::new(rules);
+        for (expr, password) in self.entries() {
+            tally.check(expr, password);
+        }
+        tally
+    }
+
+    fn outcome(&self, rule: &dyn PolicyRule) -> Outcome {
+        let valid = self.entries()
+            .filter(|(expr, password)| expr.valid(rule, password))
+            .count();
+        Outcome::new(Some(valid as Answer))
+            .with("rule", rule.name())
+            .with("total", self.entries.len())
+    }
+
+}
+
+impl Solution for Passwords {
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let entries = input
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| {
+                parse_line(line)
+                    .map(|(expr, password)| (expr, password.to_owned()))
+                    .map_err(|e| e.at_line(idx + 1))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Passwords { entries })
+    }
+
+    fn part1(&self) -> Outcome {
+        self.outcome(&Occurrences)
+    }
+
+    fn part2(&self) -> Outcome {
+        self.outcome(&Positions)
+    }
+
+}
@@ -0,0 +1,244 @@
+use std::str::FromStr;
+
+use bitset_core::BitSet;
+
+use crate::{Outcome, ParseError, Solution};
+use crate::metrics::Metric;
+
+/// Chunk of grid bits, rows are laid out back to back with no padding.
+pub type Lane = [u64; 4];
+pub type BitIdx = usize;
+
+pub const BITS_PER_LANE: usize = std::mem::size_of::<Lane>() * 8;
+
+/// Lanes intersected with the route.
+pub static LANES: Metric = Metric::counter("day3.lanes");
+
+struct StridedFmt<'a, T>(usize, &'a T);
+
+impl<'a, T> std::fmt::Display for StridedFmt<'a, T> where T: BitSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stride = self.0;
+        if stride == 0 {
+            return Ok(());
+        }
+        for bit in 0..self.1.bit_len() {
+            if bit > 0 && bit % stride == 0 {
+                f.write_str("\n")?
+            }
+            f.write_str(if self.1.bit_test(bit) { "#" } else { "." })?
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Grid {
+    v: Vec<Lane>,
+    stride: BitIdx,
+    cursor: BitIdx
+}
+
+impl std::fmt::Display for Grid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stride = self.stride;
+        writeln!(f, "Rows (stride = {}) [", stride)?;
+        write!(f, "{}", StridedFmt(stride, &self.v))?;
+        write!(f, "\n]")
+    }
+}
+
+struct GridRow<'a> {
+    slice: &'a mut [Lane],
+    offset: BitIdx,
+    stride: BitIdx
+}
+
+impl<'a> GridRow<'a> {
+
+    pub fn stride(&self) -> BitIdx {
+        self.stride
+    }
+
+    pub fn bit_set(&mut self, bit: usize) -> &mut Self {
+        self.slice.bit_set(self.offset + bit);
+        self
+    }
+
+}
+
+impl Grid {
+
+    pub fn new(stride: usize) -> Self {
+        Self { v: vec![Lane::default()], stride, cursor: 0 }
+    }
+
+    /// Iterates over lanes marking each position visited going `left` and
+    /// `down` from the column `from` in the first row, wrapping around. There
+    /// are no positions to visit in a grid with no columns.
+    pub fn each(&self, from: usize, left: usize, down: usize) -> Each {
+        Each { idx: 0, cursor: from, left, down, stride: self.stride }
+    }
+
+    pub fn stride(&self) -> BitIdx {
+        self.stride
+    }
+
+    pub fn lanes(&self) -> &[Lane] {
+        self.v.as_slice()
+    }
+
+    /// Number of lanes the grid occupies.
+    pub fn len(&self) -> usize {
+        self.v.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cursor == 0
+    }
+
+    fn next_row(&mut self) -> GridRow<'_> {
+        let cursor = self.cursor;
+        self.cursor += self.stride;
+        self.v.resize(1 + (self.cursor + self.stride - 1) / BITS_PER_LANE, Lane::default());
+        GridRow {
+            slice: self.v.as_mut_slice(),
+            offset: cursor,
+            stride: self.stride
+        }
+    }
+
+}
+
+#[derive(Debug, Clone)]
+pub struct Each {
+    idx: usize,
+    cursor: usize,
+    left: usize,
+    down: usize,
+    stride: usize
+}
+
+impl Iterator for Each {
+    type Item = Lane;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stride == 0 {
+            return None
+        }
+        let mut next = Lane::default();
+        while self.idx < BITS_PER_LANE {
+            next.bit_set(self.idx);
+            self.idx += self.down * self.stride + self.left; // Stroll down and left.
+            self.cursor += self.left; // Remember how far to the left we are.
+            let wraps = self.cursor / self.stride; // If we're past the stride already, maybe several times over...
+            self.cursor %= self.stride; // ...wrap back...
+            self.idx -= wraps * self.stride; // ...and correct current position by going back `stride` bits each time.
+        }
+        self.idx -= BITS_PER_LANE;
+        Some(next)
+    }
+}
+
+impl FromStr for Grid {
+
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+
+        fn parse_line(line: &str, mut slice: GridRow) -> Result<(), ParseError> {
+            let length = line.len();
+            let stride = slice.stride();
+            if length != stride {
+                return Err(ParseError::new("Irregular input")
+                    .at_column(length.min(stride) + 1)
+                    .expected(format!("row {} bytes long", stride))
+                    .found(format!("{} bytes", length)))
+            }
+            for (idx, c) in (0..stride).zip(line.chars()) {
+                match c {
+                    '.' => {},
+                    '#' => { slice.bit_set(idx); },
+                    _ => {
+                        return Err(ParseError::new("Invalid char in input")
+                            .at_column(idx + 1)
+                            .expected("'.' or '#'")
+                            .found_char(Some(c)))
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        let mut lines = s.lines();
+        if let Some(first) = lines.next() {
+            let stride = first.len();
+            if stride == 0 {
+                return Err(ParseError::new("Empty row")
+                    .at_line(1)
+                    .at_column(1)
+                    .with_snippet(first)
+                    .expected("'.' or '#'")
+                    .found_char(None))
+            }
+            let mut grid = Grid::new(stride);
+            for (idx, line) in std::iter::once(first).chain(lines).enumerate() {
+                parse_line(line, grid.next_row())
+                    .map_err(|e| e.at_line(idx + 1).with_snippet(line))?;
+            }
+            Ok(grid)
+        }
+        else {
+            Ok(Grid::new(0))
+        }
+
+    }
+
+}
+
+#[inline(never)]
+pub fn count_hits(grid: &Grid, each: Each) -> usize {
+    if grid.stride() == 0 {
+        return 0
+    }
+    LANES.add(grid.len());
+    let mut path: Vec<Lane> = each.take(grid.len()).collect();
+    let lanes = grid.v.as_slice();
+    path.as_mut_slice().bit_and(lanes).bit_count()
+}
+
+#[derive(Debug, Clone)]
+pub struct Forest {
+    grid: Grid
+}
+
+impl Forest {
+
+    pub fn new(grid: Grid) -> Self {
+        Forest { grid }
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn hits(&self, (right, down): (usize, usize)) -> usize {
+        count_hits(&self.grid, self.grid.each(0, right, down))
+    }
+
+}
+
+impl Solution for Forest {
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Forest { grid: input.parse()? })
+    }
+
+    fn part1(&self) -> Outcome {
+        super::part1(|slope| self.hits(slope))
+    }
+
+    fn part2(&self) -> Outcome {
+        super::part2(|slope| self.hits(slope))
+    }
+
+}
@@ -0,0 +1,222 @@
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt::{Display, Formatter},
+    str::FromStr
+};
+
+use crate::{Outcome, ParseError, Solution};
+use crate::metrics::Metric;
+
+/// Tiles visited along routes.
+pub static TILES: Metric = Metric::counter("day3.tiles");
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Tile {
+    #[default]
+    Open,
+    Tree
+}
+
+impl Display for Tile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tile::Open => write!(f, "."),
+            Tile::Tree => write!(f, "#")
+        }
+    }
+}
+
+impl TryFrom<char> for Tile {
+    type Error = ParseError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Tile::Open),
+            '#' => Ok(Tile::Tree),
+            _ => Err(ParseError::new("Invalid tile").expected("'.' or '#'").found_char(Some(c)))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Coord {
+    pub x: usize,
+    pub y: usize
+}
+
+impl From<(usize, usize)> for Coord {
+    fn from((x, y): (usize, usize)) -> Self {
+        Self { x, y }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Grid {
+    size: Coord,
+    tiles: Vec<Tile>
+}
+
+impl Grid {
+
+    pub fn new(size: impl Into<Coord>) -> Grid {
+        let size = size.into();
+        Grid {
+            size,
+            tiles: vec![Tile::default(); size.x * size.y]
+        }
+    }
+
+    pub fn size(&self) -> Coord {
+        self.size
+    }
+
+    pub fn get(&self, coord: impl Into<Coord>) -> Tile {
+        self.tiles[self.index(coord.into())]
+    }
+
+    pub fn set(&mut self, coord: impl Into<Coord>, tile: Tile) {
+        let index = self.index(coord.into());
+        self.tiles[index] = tile
+    }
+
+    pub fn route(&self, step: impl Into<Coord>) -> impl Iterator<Item = Tile> + '_ {
+        Route::new(self.size, step.into()).map(move |coord| self.get(coord))
+    }
+
+    fn index(&self, coord: Coord) -> usize {
+        assert!(coord.y < self.size.y);
+        coord.y * self.size.x + coord.x % self.size.x
+    }
+
+}
+
+impl Display for Grid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                self.get((x, y)).fmt(f)?
+            }
+            writeln!(f)?
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Grid {
+
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+
+        let height = s.lines().count();
+        let mut width: Option<usize> = None;
+        for (y, line) in s.lines().enumerate() {
+            let w = line.len();
+            match width {
+                Some(w0) => {
+                    if w0 != w {
+                        return Err(ParseError::new("Irregular input")
+                            .at_line(y + 1)
+                            .at_column(w0.min(w) + 1)
+                            .with_snippet(line)
+                            .expected(format!("row {} bytes long", w0))
+                            .found(format!("{} bytes", w)))
+                    }
+                }
+                None if w == 0 => {
+                    return Err(ParseError::new("Empty row")
+                        .at_line(y + 1)
+                        .at_column(1)
+                        .with_snippet(line)
+                        .expected("'.' or '#'")
+                        .found_char(None))
+                }
+                None => {
+                    width = Some(w)
+                }
+            }
+        }
+
+        let width = width.unwrap_or(0);
+        let mut grid = Grid::new((width, height));
+        for (y, line) in s.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let tile: Tile = c.try_into()
+                    .map_err(|e: ParseError| e.at_line(y + 1).at_column(x + 1).with_snippet(line))?;
+                grid.set((x, y), tile)
+            }
+        }
+
+        Ok(grid)
+    }
+
+}
+
+#[derive(Debug, Clone)]
+pub struct Route {
+    size: Coord,
+    coord: Coord,
+    step: Coord
+}
+
+impl Route {
+    pub fn new(size: Coord, step: Coord) -> Self {
+        Self { size, coord: (0, 0).into(), step }
+    }
+}
+
+impl Iterator for Route {
+
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.coord.y < self.size.y {
+            TILES.inc();
+            let next = Some(self.coord);
+            self.coord.x += self.step.x;
+            self.coord.y += self.step.y;
+            next
+        }
+        else {
+            None
+        }
+    }
+
+}
+
+#[derive(Debug, Clone)]
+pub struct Forest {
+    grid: Grid
+}
+
+impl Forest {
+
+    pub fn new(grid: Grid) -> Self {
+        Forest { grid }
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn hits(&self, slope: (usize, usize)) -> usize {
+        self.grid.route(slope).filter(|&tile| tile == Tile::Tree).count()
+    }
+
+}
+
+impl Solution for Forest {
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Forest { grid: input.parse()? })
+    }
+
+    fn part1(&self) -> Outcome {
+        super::part1(|slope| self.hits(slope))
+    }
+
+    fn part2(&self) -> Outcome {
+        super::part2(|slope| self.hits(slope))
+    }
+
+}
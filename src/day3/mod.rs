@@ -0,0 +1,46 @@
+//! Day 3: Toboggan Trajectory, counting trees hit going down the slope.
+//!
+//! There are two interchangeable grid backends: [`dense`] keeping a tile per
+//! cell, and [`bitset`] packing rows into bit lanes so that a whole route is
+//! intersected with the grid at once.
+
+pub mod bitset;
+pub mod dense;
+
+use std::convert::TryFrom;
+
+use crate::{Answer, Outcome};
+use crate::metrics::Metric;
+
+/// Every day 3 metric of both backends, for [`crate::metrics`] to reset and
+/// report.
+pub static METRICS: &[&Metric] = &[&bitset::LANES, &dense::TILES];
+
+/// Slope (right, down) taken down the slope in the first part.
+pub const SLOPE: (usize, usize) = (3, 1);
+
+/// Slopes checked in the second part, their hits multiplied together.
+pub const SLOPES: [(usize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
+pub(crate) fn part1(hits: impl Fn((usize, usize)) -> usize) -> Outcome {
+    let hits = hits(SLOPE);
+    Outcome::new(Some(hits as Answer))
+        .with("slope", SLOPE)
+        .with("hits", hits)
+}
+
+/// Product of hits along several slopes, unless it overflows.
+pub fn product(hits: &[usize]) -> Option<Answer> {
+    hits.iter().try_fold(1 as Answer, |product, &h| product.checked_mul(Answer::try_from(h).ok()?))
+}
+
+pub(crate) fn part2(hits: impl Fn((usize, usize)) -> usize) -> Outcome {
+    let hits: Vec<usize> = SLOPES.iter().map(|&slope| hits(slope)).collect();
+    let outcome = match product(&hits) {
+        Some(product) => Outcome::new(Some(product)),
+        None => Outcome::overflowed()
+    };
+    outcome
+        .with("slopes", SLOPES.to_vec())
+        .with("hits", hits)
+}
@@ -0,0 +1,270 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::Format;
+use crate::json::Json;
+
+/// Diagnostic about malformed puzzle input.
+///
+/// Carries as much of the location as is known at the point of failure:
+/// parsers closer to the input fill in line and column, callers knowing
+/// where the input came from add the file name on the way up. When the
+/// offending line is attached the error renders it with a caret under the
+/// offending character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError(Box<Diagnostic>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Diagnostic {
+    message: String,
+    file: Option<String>,
+    line: Option<usize>,
+    column: Option<usize>,
+    snippet: Option<String>,
+    expected: Option<String>,
+    found: Option<String>
+}
+
+impl ParseError {
+
+    pub fn new(message: impl Into<String>) -> Self {
+        ParseError(Box::new(Diagnostic {
+            message: message.into(),
+            file: None,
+            line: None,
+            column: None,
+            snippet: None,
+            expected: None,
+            found: None
+        }))
+    }
+
+    /// Names the file the input came from.
+    pub fn in_file(mut self, file: impl Into<String>) -> Self {
+        self.0.file = Some(file.into());
+        self
+    }
+
+    /// Line number, counting from 1.
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.0.line = Some(line);
+        self
+    }
+
+    /// Column number in characters, counting from 1.
+    pub fn at_column(mut self, column: usize) -> Self {
+        self.0.column = Some(column);
+        self
+    }
+
+    /// Text of the offending line, to be rendered along with the message.
+    pub fn with_snippet(mut self, snippet: impl Into<String>) -> Self {
+        self.0.snippet = Some(snippet.into());
+        self
+    }
+
+    pub fn expected(mut self, expected: impl Into<String>) -> Self {
+        self.0.expected = Some(expected.into());
+        self
+    }
+
+    pub fn found(mut self, found: impl Into<String>) -> Self {
+        self.0.found = Some(found.into());
+        self
+    }
+
+    /// Describes the offending character, `None` meaning the line ended early.
+    pub fn found_char(self, c: Option<char>) -> Self {
+        match c {
+            Some(c) => self.found(format!("{:?}", c)),
+            None => self.found("end of line")
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.0.message
+    }
+
+    pub fn file(&self) -> Option<&str> {
+        self.0.file.as_deref()
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.0.line
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.0.column
+    }
+
+    pub fn to_json(&self) -> Json {
+        let d = &self.0;
+        Json::object()
+            .with("message", d.message.as_str())
+            .with("file", d.file.clone())
+            .with("line", d.line)
+            .with("column", d.column)
+            .with("expected", d.expected.clone())
+            .with("found", d.found.clone())
+    }
+
+}
+
+impl From<peg::error::ParseError<peg::str::LineCol>> for ParseError {
+    fn from(e: peg::error::ParseError<peg::str::LineCol>) -> Self {
+        ParseError::new("Syntax error")
+            .at_line(e.location.line)
+            .at_column(e.location.column)
+            .expected(e.expected.to_string())
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.0.file {
+            write!(f, "{}:", file)?;
+        }
+        if let Some(line) = self.0.line {
+            write!(f, "{}:", line)?;
+            if let Some(column) = self.0.column {
+                write!(f, "{}:", column)?;
+            }
+        }
+        if self.0.file.is_some() || self.0.line.is_some() {
+            f.write_str(" ")?;
+        }
+        f.write_str(&self.0.message)?;
+        match (&self.0.expected, &self.0.found) {
+            (Some(expected), Some(found)) => write!(f, ": expected {}, found {}", expected, found)?,
+            (Some(expected), None) => write!(f, ": expected {}", expected)?,
+            (None, Some(found)) => write!(f, ": found {}", found)?,
+            (None, None) => {}
+        }
+        if let Some(snippet) = &self.0.snippet {
+            let gutter = self.0.line.map(|l| l.to_string()).unwrap_or_default();
+            let pad = " ".repeat(gutter.len());
+            write!(f, "\n{} |\n{} | {}", pad, gutter, snippet)?;
+            if let Some(column) = self.0.column {
+                let indent: String = snippet.chars()
+                    .take(column.saturating_sub(1))
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+                write!(f, "\n{} | {}^", pad, indent)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Everything that can go wrong running a solver, each with its own exit code
+/// so that scripts driving the binaries can tell the cases apart.
+#[derive(Debug)]
+pub enum Error {
+    /// Command line does not make sense.
+    Usage(String),
+    /// Input could not be read.
+    Io { path: String, source: std::io::Error },
+    /// Input was read but is malformed.
+    Parse(ParseError),
+    /// Input is fine but there's no answer in it.
+    NoSolution(String),
+    /// There is an answer but it does not fit into an integer.
+    Overflow(String),
+    /// Answers do not match what's expected.
+    Mismatch(String)
+}
+
+impl Error {
+
+    pub const EXIT_MISMATCH: i32 = 1;
+    pub const EXIT_NO_SOLUTION: i32 = 2;
+    pub const EXIT_OVERFLOW: i32 = 3;
+    pub const EXIT_USAGE: i32 = 64;
+    pub const EXIT_PARSE: i32 = 65;
+    pub const EXIT_NO_INPUT: i32 = 66;
+    pub const EXIT_IO: i32 = 74;
+
+    pub fn usage(reason: impl Into<String>) -> Self {
+        Error::Usage(reason.into())
+    }
+
+    pub fn io(path: impl Into<String>, source: std::io::Error) -> Self {
+        Error::Io { path: path.into(), source }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Usage(_) => "usage",
+            Error::Io { .. } => "io",
+            Error::Parse(_) => "parse",
+            Error::NoSolution(_) => "no_solution",
+            Error::Overflow(_) => "overflow",
+            Error::Mismatch(_) => "mismatch"
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        let error = match self {
+            Error::Parse(e) => e.to_json(),
+            _ => Json::object().with("message", self.to_string())
+        };
+        Json::object()
+            .with("error", error.with("kind", self.kind()))
+            .with("exit_code", self.exit_code())
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Usage(_) => Self::EXIT_USAGE,
+            Error::Io { source, .. } if source.kind() == std::io::ErrorKind::NotFound => Self::EXIT_NO_INPUT,
+            Error::Io { .. } => Self::EXIT_IO,
+            Error::Parse(_) => Self::EXIT_PARSE,
+            Error::NoSolution(_) => Self::EXIT_NO_SOLUTION,
+            Error::Overflow(_) => Self::EXIT_OVERFLOW,
+            Error::Mismatch(_) => Self::EXIT_MISMATCH
+        }
+    }
+
+    /// Reports the error and exits the process. In JSON format the error is
+    /// reported as an object on stdout, otherwise it goes to stderr along
+    /// with the `usage` if the command line was at fault.
+    pub fn exit(self, usage: &str, format: Format) -> ! {
+        match (format, &self) {
+            (Format::Json, _) => println!("{}", self.to_json()),
+            (_, Error::Usage(_)) => eprintln!("{}\n\n{}", self, usage),
+            (_, _) => eprintln!("{}", self)
+        }
+        std::process::exit(self.exit_code())
+    }
+
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Usage(reason) => f.write_str(reason),
+            Error::Io { path, source } => write!(f, "{}: {}", path, source),
+            Error::Parse(e) => e.fmt(f),
+            Error::NoSolution(reason) => write!(f, "No solution: {}", reason),
+            Error::Overflow(reason) => write!(f, "Overflow: {}", reason),
+            Error::Mismatch(reason) => f.write_str(reason)
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
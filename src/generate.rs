@@ -0,0 +1,242 @@
+//! Synthetic puzzle inputs of arbitrary size, for stress tests and benchmarks.
+//!
+//! Every generator is driven by [`Rng`] so that the same seed always produces
+//! the same input.
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+/// SplitMix64, small and fast enough, and most importantly reproducible
+/// across platforms and releases.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed number in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0);
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    /// Uniformly distributed number in `lo..=hi`.
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        assert!(lo <= hi);
+        lo.wrapping_add(self.below(hi.wrapping_sub(lo) as u64 + 1) as i64)
+    }
+
+    /// Uniformly distributed number in `0.0..1.0`.
+    pub fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.unit() < p
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+}
+
+/// Day 1: list of distinct expenses with `k` of them planted to sum up to the
+/// `target`, the rest drawn from `1..=max`.
+#[derive(Debug, Clone)]
+pub struct Expenses {
+    pub count: usize,
+    pub k: usize,
+    pub target: i64,
+    pub max: i64
+}
+
+impl Expenses {
+
+    pub fn check(&self) -> Result<(), String> {
+        if self.k == 0 || self.k > self.count {
+            return Err(format!("Planted sum needs 1 to {} expenses, not {}", self.count, self.k));
+        }
+        let k = self.k as i64;
+        if self.target < k * (k + 1) / 2 {
+            return Err(format!("Target {} is too small to be a sum of {} distinct expenses", self.target, k));
+        }
+        if self.max < self.count as i64 {
+            return Err(format!("Range 1..={} is too narrow for {} distinct expenses", self.max, self.count));
+        }
+        Ok(())
+    }
+
+    /// Values summing up to the target, all distinct. Starts off with
+    /// `1..=k` and spreads what's left of the target over random suffixes of
+    /// it, which keeps the values ascending and so distinct.
+    pub fn planted(&self, rng: &mut Rng) -> Vec<i64> {
+        let k = self.k as i64;
+        let mut surplus = self.target - k * (k + 1) / 2;
+        let mut steps = vec![0; self.k];
+        while surplus > 0 {
+            let len = 1 + rng.below(surplus.min(k) as u64) as i64;
+            let amount = rng.range(1, surplus / len);
+            steps[(k - len) as usize] += amount;
+            surplus -= amount * len;
+        }
+        let mut raised = 0;
+        (1..=k).zip(steps).map(|(v, step)| {
+            raised += step;
+            v + raised
+        }).collect()
+    }
+
+    pub fn generate(&self, rng: &mut Rng) -> Vec<i64> {
+        let mut values = self.planted(rng);
+        let mut seen: HashSet<i64> = values.iter().copied().collect();
+        let max = self.max.max(self.target);
+        while values.len() < self.count {
+            let value = rng.range(1, max);
+            if seen.insert(value) {
+                values.push(value);
+            }
+        }
+        rng.shuffle(&mut values);
+        values
+    }
+
+    pub fn write(&self, rng: &mut Rng, out: &mut impl Write) -> io::Result<()> {
+        for value in self.generate(rng) {
+            writeln!(out, "{}", value)?;
+        }
+        Ok(())
+    }
+
+}
+
+/// Which interpretation of a day 2 policy the ratio of valid passwords
+/// is controlled for.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Rule {
+    Occurrences,
+    Positions
+}
+
+/// Day 2: password policy file, exactly `round(count * valid_ratio)` lines
+/// valid under the chosen `rule`.
+#[derive(Debug, Clone)]
+pub struct Passwords {
+    pub count: usize,
+    pub valid_ratio: f64,
+    pub rule: Rule
+}
+
+fn letter(rng: &mut Rng) -> char {
+    (b'a' + rng.below(26) as u8) as char
+}
+
+fn letter_except(rng: &mut Rng, c: char) -> char {
+    loop {
+        let l = letter(rng);
+        if l != c {
+            return l;
+        }
+    }
+}
+
+impl Passwords {
+
+    pub fn check(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.valid_ratio) {
+            return Err(format!("Valid ratio must be within 0 and 1, not {}", self.valid_ratio));
+        }
+        Ok(())
+    }
+
+    fn line(&self, rng: &mut Rng, valid: bool) -> String {
+        let c = letter(rng);
+        let lo = rng.range(1, 8) as usize;
+        let hi = rng.range(lo as i64 + 1, lo as i64 + 10) as usize;
+        let password: String = match self.rule {
+            Rule::Positions => {
+                let length = rng.range(hi as i64, hi as i64 + 8) as usize;
+                let mut password: Vec<char> = (0..length).map(|_| letter(rng)).collect();
+                let (at_lo, at_hi) = match (valid, rng.chance(0.5)) {
+                    (true, true) => (true, false),
+                    (true, false) => (false, true),
+                    (false, both) => (both, both)
+                };
+                password[lo - 1] = if at_lo { c } else { letter_except(rng, c) };
+                password[hi - 1] = if at_hi { c } else { letter_except(rng, c) };
+                password.into_iter().collect()
+            },
+            Rule::Occurrences => {
+                let occurrences = match (valid, lo > 1 && rng.chance(0.5)) {
+                    (true, _) => rng.range(lo as i64, hi as i64) as usize,
+                    (false, true) => rng.range(0, lo as i64 - 1) as usize,
+                    (false, false) => rng.range(hi as i64 + 1, hi as i64 + 5) as usize
+                };
+                let length = occurrences + rng.range(1, 8) as usize;
+                let mut password: Vec<char> = (0..length)
+                    .map(|i| if i < occurrences { c } else { letter_except(rng, c) })
+                    .collect();
+                rng.shuffle(&mut password);
+                password.into_iter().collect()
+            }
+        };
+        format!("{}-{} {}: {}", lo, hi, c, password)
+    }
+
+    pub fn write(&self, rng: &mut Rng, out: &mut impl Write) -> io::Result<()> {
+        let valid = (self.count as f64 * self.valid_ratio).round() as usize;
+        let mut validity: Vec<bool> = (0..self.count).map(|i| i < valid).collect();
+        rng.shuffle(&mut validity);
+        for valid in validity {
+            writeln!(out, "{}", self.line(rng, valid))?;
+        }
+        Ok(())
+    }
+
+}
+
+/// Day 3: map of the slope, each tile a tree with the probability `density`.
+#[derive(Debug, Clone)]
+pub struct Forest {
+    pub width: usize,
+    pub height: usize,
+    pub density: f64
+}
+
+impl Forest {
+
+    pub fn check(&self) -> Result<(), String> {
+        if self.width == 0 {
+            return Err("Width must be positive".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.density) {
+            return Err(format!("Density must be within 0 and 1, not {}", self.density));
+        }
+        Ok(())
+    }
+
+    pub fn write(&self, rng: &mut Rng, out: &mut impl Write) -> io::Result<()> {
+        let mut row = String::with_capacity(self.width);
+        for _ in 0..self.height {
+            row.clear();
+            row.extend((0..self.width).map(|_| if rng.chance(self.density) { '#' } else { '.' }));
+            writeln!(out, "{}", row)?;
+        }
+        Ok(())
+    }
+
+}
@@ -0,0 +1,53 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::Error;
+
+/// Where the puzzle input comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Stdin,
+    File(String)
+}
+
+impl Source {
+
+    /// Interprets a command line argument: no path at all or `-` stands for
+    /// the standard input.
+    pub fn from_arg(arg: Option<String>) -> Self {
+        match arg {
+            None => Source::Stdin,
+            Some(path) if path == "-" => Source::Stdin,
+            Some(path) => Source::File(path)
+        }
+    }
+
+    /// Name to refer to the input by in diagnostics.
+    pub fn name(&self) -> &str {
+        match self {
+            Source::Stdin => "<stdin>",
+            Source::File(path) => path
+        }
+    }
+
+    /// Reads the whole input in.
+    pub fn read_to_string(&self) -> Result<String, Error> {
+        let mut input = String::new();
+        self.open()?
+            .read_to_string(&mut input)
+            .map_err(|e| Error::io(self.name(), e))?;
+        Ok(input)
+    }
+
+    /// Opens the input for streaming, line by line for example.
+    pub fn open(&self) -> Result<Box<dyn BufRead>, Error> {
+        match self {
+            Source::Stdin => Ok(Box::new(std::io::stdin().lock())),
+            Source::File(path) => {
+                let file = File::open(path).map_err(|e| Error::io(path.as_str(), e))?;
+                Ok(Box::new(BufReader::new(file)))
+            }
+        }
+    }
+
+}
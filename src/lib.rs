@@ -0,0 +1,189 @@
+//! Advent of Code 2020 solutions.
+//!
+//! Every day lives in its own module exposing the parsed puzzle types and the
+//! solvers working on them, e.g. [`day1::SetIterator::find_n_sum`] or
+//! [`day3::bitset::count_hits`]. On top of that each day implements
+//! [`Solution`], which is what the `xmascode` runner drives through [`DAYS`].
+
+pub mod answers;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod generate;
+pub mod json;
+pub mod metrics;
+pub mod timing;
+
+mod error;
+mod input;
+
+pub use error::{Error, ParseError};
+pub use input::Source;
+
+use json::Json;
+
+pub type Answer = i64;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Part {
+    One,
+    Two
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl From<Part> for Json {
+    fn from(part: Part) -> Self {
+        match part {
+            Part::One => Json::Int(1),
+            Part::Two => Json::Int(2)
+        }
+    }
+}
+
+impl std::fmt::Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => f.write_str("1"),
+            Part::Two => f.write_str("2")
+        }
+    }
+}
+
+/// How results are reported.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    Text,
+    Tsv,
+    Json
+}
+
+impl Format {
+
+    /// Pulls `--format <FORMAT>` out of the command line arguments, if it's
+    /// there.
+    pub fn take(args: &mut Vec<String>) -> Result<Option<Format>, Error> {
+        match args.iter().position(|arg| arg == "--format") {
+            Some(idx) => {
+                args.remove(idx);
+                if idx == args.len() {
+                    return Err(Error::usage("Please provide output format"));
+                }
+                args.remove(idx).parse().map(Some)
+            },
+            None => Ok(None)
+        }
+    }
+
+}
+
+impl std::str::FromStr for Format {
+
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "tsv" => Ok(Format::Tsv),
+            "json" => Ok(Format::Json),
+            _ => Err(Error::usage(format!("Invalid format: {}", s)))
+        }
+    }
+
+}
+
+/// Answer to a puzzle part, along with the intermediate values that led to it.
+#[derive(Debug, Clone, Default)]
+pub struct Outcome {
+    pub answer: Option<Answer>,
+    /// There is an answer, only it does not fit into [`Answer`].
+    pub overflow: bool,
+    pub details: Vec<(&'static str, Json)>
+}
+
+impl Outcome {
+
+    pub fn new(answer: Option<Answer>) -> Self {
+        Outcome { answer, overflow: false, details: Vec::new() }
+    }
+
+    /// Outcome of a part solved with an answer too large to report.
+    pub fn overflowed() -> Self {
+        Outcome { answer: None, overflow: true, details: Vec::new() }
+    }
+
+    pub fn with(mut self, key: &'static str, value: impl Into<Json>) -> Self {
+        self.details.push((key, value.into()));
+        self
+    }
+
+    pub fn details_json(&self) -> Json {
+        Json::Object(self.details.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+    }
+
+}
+
+/// A puzzle solver: parses the input once, then answers both parts off of it.
+pub trait Solution {
+
+    fn parse(input: &str) -> Result<Self, ParseError> where
+        Self: Sized;
+
+    fn part1(&self) -> Outcome;
+
+    fn part2(&self) -> Outcome;
+
+    fn solve(&self, part: Part) -> Outcome {
+        match part {
+            Part::One => self.part1(),
+            Part::Two => self.part2()
+        }
+    }
+
+}
+
+/// An entry in the registry, a single solver for a specific day.
+#[derive(Debug)]
+pub struct Day {
+    pub day: u8,
+    pub name: &'static str,
+    parse: fn(&str) -> Result<Box<dyn Solution>, ParseError>
+}
+
+impl Day {
+
+    pub fn parse(&self, input: &str) -> Result<Box<dyn Solution>, ParseError> {
+        (self.parse)(input)
+    }
+
+    /// Conventional location of the puzzle input for this day.
+    pub fn input(&self) -> String {
+        format!("input/day{}", self.day)
+    }
+
+}
+
+fn parse_boxed<S: Solution + 'static>(input: &str) -> Result<Box<dyn Solution>, ParseError> {
+    Ok(Box::new(S::parse(input)?))
+}
+
+/// All the registered solvers, ordered by day. The first one registered for
+/// a day is the default one for that day.
+pub const DAYS: &[Day] = &[
+    Day { day: 1, name: "expenses", parse: parse_boxed::<day1::Expenses> },
+    Day { day: 2, name: "passwords", parse: parse_boxed::<day2::Passwords> },
+    Day { day: 3, name: "dense", parse: parse_boxed::<day3::dense::Forest> },
+    Day { day: 3, name: "bitset", parse: parse_boxed::<day3::bitset::Forest> }
+];
+
+/// Default solver for the given day.
+pub fn day(day: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+/// Specific solver for the given day.
+pub fn solver(day: u8, name: &str) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day && d.name == name)
+}
@@ -0,0 +1,125 @@
+//! Named counters and gauges solvers use to tell how much work they've done.
+//!
+//! Metrics are plain statics living next to the code they measure. Every day
+//! module lists its own in a `METRICS` slice, and those are gathered in
+//! [`METRICS`] so that they can be reset and reported all at once. Updates
+//! are relaxed atomic operations, cheap enough to be done in the innermost
+//! loops.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use crate::{day1, day2, day3};
+use crate::json::Json;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Kind {
+    /// Only ever goes up, e.g. number of iterations.
+    Counter,
+    /// Holds the last value set, e.g. size of the input.
+    Gauge
+}
+
+#[derive(Debug)]
+pub struct Metric {
+    name: &'static str,
+    kind: Kind,
+    value: AtomicI64
+}
+
+impl Metric {
+
+    pub const fn counter(name: &'static str) -> Self {
+        Metric { name, kind: Kind::Counter, value: AtomicI64::new(0) }
+    }
+
+    pub const fn gauge(name: &'static str) -> Self {
+        Metric { name, kind: Kind::Gauge, value: AtomicI64::new(0) }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn inc(&self) {
+        self.add(1)
+    }
+
+    #[inline]
+    pub fn add(&self, n: usize) {
+        debug_assert_eq!(self.kind, Kind::Counter);
+        let _ = self.value.fetch_add(n as i64, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn set(&self, value: usize) {
+        debug_assert_eq!(self.kind, Kind::Gauge);
+        self.value.store(value as i64, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        self.value.store(0, Ordering::Release);
+    }
+
+}
+
+/// Metrics of every day, as listed by the day modules.
+pub static METRICS: &[&[&Metric]] = &[day1::METRICS, day2::METRICS, day3::METRICS];
+
+/// Every metric there is.
+pub fn all() -> impl Iterator<Item = &'static Metric> {
+    METRICS.iter().flat_map(|metrics| metrics.iter().copied())
+}
+
+/// Resets every metric, to be done before each run.
+pub fn reset() {
+    for metric in all() {
+        metric.reset();
+    }
+}
+
+/// Values of the metrics touched since the last reset.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot(Vec<(&'static str, i64)>);
+
+impl Snapshot {
+
+    pub fn take() -> Self {
+        Snapshot(all()
+            .map(|metric| (metric.name(), metric.get()))
+            .filter(|&(_, value)| value != 0)
+            .collect())
+    }
+
+    pub fn values(&self) -> &[(&'static str, i64)] {
+        &self.0
+    }
+
+    pub fn get(&self, name: &str) -> Option<i64> {
+        self.0.iter().find(|(n, _)| *n == name).map(|&(_, value)| value)
+    }
+
+    /// Combines two snapshots, values from `other` taking precedence.
+    pub fn merge(&self, other: &Snapshot) -> Snapshot {
+        let mut merged: Vec<_> = self.0.iter()
+            .filter(|(name, _)| other.get(name).is_none())
+            .copied()
+            .collect();
+        merged.extend(other.0.iter().copied());
+        merged.sort_by_key(|(name, _)| all().position(|m| m.name() == *name));
+        Snapshot(merged)
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(self.0.iter().map(|&(name, value)| (name.to_string(), value.into())).collect())
+    }
+
+}
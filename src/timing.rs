@@ -0,0 +1,123 @@
+//! Per-phase timing of solver runs: parsing the input and answering each part.
+
+use std::time::Duration;
+
+use devtimer::DevTime;
+
+use crate::{Day, ParseError, Part, Solution};
+use crate::json::Json;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Phase {
+    Parse,
+    Solve(Part)
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::Parse => f.write_str("parse"),
+            Phase::Solve(part) => write!(f, "part{}", part)
+        }
+    }
+}
+
+/// Durations of repeated runs of a single phase, in nanoseconds.
+#[derive(Debug, Clone, Default)]
+pub struct Samples(Vec<u128>);
+
+impl Samples {
+
+    pub fn push(&mut self, nanos: u128) {
+        let idx = self.0.partition_point(|&n| n <= nanos);
+        self.0.insert(idx, nanos);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn min(&self) -> u128 {
+        self.0.first().copied().unwrap_or(0)
+    }
+
+    pub fn median(&self) -> u128 {
+        match self.0.len() {
+            0 => 0,
+            n if n % 2 == 0 => (self.0[n / 2 - 1] + self.0[n / 2]) / 2,
+            n => self.0[n / 2]
+        }
+    }
+
+    pub fn max(&self) -> u128 {
+        self.0.last().copied().unwrap_or(0)
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::object()
+            .with("min_ns", self.min())
+            .with("median_ns", self.median())
+            .with("max_ns", self.max())
+    }
+
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub repeat: usize,
+    pub phases: Vec<(Phase, Samples)>
+}
+
+impl Report {
+
+    pub fn to_json(&self) -> Json {
+        self.phases.iter().fold(
+            Json::object().with("repeat", self.repeat),
+            |json, (phase, samples)| json.with(phase.to_string(), samples.to_json())
+        )
+    }
+
+}
+
+/// Runs `f` once and tells how long it took, in nanoseconds.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, u128) {
+    let mut timer = DevTime::new_simple();
+    timer.start();
+    let result = f();
+    timer.stop();
+    (result, timer.time_in_nanos().unwrap_or(0))
+}
+
+/// Parses the input and solves each of the `parts`, every phase repeated
+/// `repeat` times. Returns the solution from the last parse along with the
+/// timings.
+pub fn time(day: &Day, input: &str, parts: &[Part], repeat: usize) -> Result<(Box<dyn Solution>, Report), ParseError> {
+    let repeat = repeat.max(1);
+    let mut samples = Samples::default();
+    let mut solution = None;
+    for _ in 0..repeat {
+        let (parsed, nanos) = measure(|| day.parse(input));
+        samples.push(nanos);
+        solution = Some(parsed?);
+    }
+    let solution = solution.expect("at least one run");
+    let mut phases = vec![(Phase::Parse, samples)];
+    for &part in parts {
+        let mut samples = Samples::default();
+        for _ in 0..repeat {
+            let (_, nanos) = measure(|| std::hint::black_box(solution.solve(part)));
+            samples.push(nanos);
+        }
+        phases.push((Phase::Solve(part), samples));
+    }
+    Ok((solution, Report { repeat, phases }))
+}
+
+/// Formats nanoseconds in the most fitting unit, e.g. `1.234ms`.
+pub fn human(nanos: u128) -> String {
+    format!("{:.3?}", Duration::from_nanos(nanos as u64))
+}